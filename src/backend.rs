@@ -0,0 +1,879 @@
+use anyhow::Result;
+use notify_rust::Notification;
+use std::process::Command;
+
+use crate::{PreparedNotification, WarningSink};
+
+/// Freedesktop category hint used when none is given, so notification
+/// daemons can route our notifications like chat messages.
+pub const DEFAULT_CATEGORY: &str = "im.received";
+
+/// Sound name that makes the notification play the OS's default sound:
+/// the `NSUserNotification` default on macOS, the freedesktop sound theme's
+/// message sound elsewhere.
+#[cfg(target_os = "macos")]
+const SYSTEM_SOUND_NAME: &str = "NSUserNotificationDefaultSoundName";
+#[cfg(target_os = "windows")]
+const SYSTEM_SOUND_NAME: &str = "Default";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SYSTEM_SOUND_NAME: &str = "message-new-instant";
+
+/// Action keys for opening the action URL.
+const DEFAULT_ACTION: &str = "default";
+const OPEN_ACTION: &str = "open";
+
+/// The mechanism used to display the notification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// The platform default (currently `notify-rust`).
+    #[default]
+    Auto,
+    NotifyRust,
+    /// AppleScript's `display notification` via `osascript`.
+    Osascript,
+    /// The `terminal-notifier` command line tool.
+    TerminalNotifier,
+}
+
+impl Backend {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Backend::Auto),
+            "notify-rust" => Some(Backend::NotifyRust),
+            "osascript" => Some(Backend::Osascript),
+            "terminal-notifier" => Some(Backend::TerminalNotifier),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Backend::Auto => "auto",
+            Backend::NotifyRust => "notify-rust",
+            Backend::Osascript => "osascript",
+            Backend::TerminalNotifier => "terminal-notifier",
+        }
+    }
+}
+
+/// How long a notification stays on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotificationStyle {
+    /// Dismissed automatically after a few seconds.
+    #[default]
+    Banner,
+    /// Stays on screen until the user dismisses it.
+    Alert,
+}
+
+impl NotificationStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "banner" => Some(NotificationStyle::Banner),
+            "alert" => Some(NotificationStyle::Alert),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            NotificationStyle::Banner => "banner",
+            NotificationStyle::Alert => "alert",
+        }
+    }
+}
+
+/// Picks the backend that can honor `style`. On macOS the banner/alert
+/// choice is a per-app system setting that notify-rust can't override, so
+/// `auto` alerts go through `osascript`'s `display alert` instead. Explicit
+/// backends are kept as chosen.
+pub fn backend_for_style(backend: Backend, style: NotificationStyle) -> Backend {
+    match (backend, style) {
+        (Backend::Auto, NotificationStyle::Alert) if cfg!(target_os = "macos") => {
+            Backend::Osascript
+        }
+        _ => backend,
+    }
+}
+
+/// A freedesktop hint value from `--hint`, typed by how it looks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintValue {
+    Int(i32),
+    Bool(bool),
+    String(String),
+}
+
+impl HintValue {
+    fn infer(value: &str) -> Self {
+        if let Ok(b) = value.parse::<bool>() {
+            HintValue::Bool(b)
+        } else if let Ok(n) = value.parse::<i32>() {
+            HintValue::Int(n)
+        } else {
+            HintValue::String(value.to_string())
+        }
+    }
+}
+
+/// Parses a `key=value` hint, inferring whether the value is a boolean, an
+/// integer, or a string.
+pub fn parse_hint(s: &str) -> Result<(String, HintValue)> {
+    let Some((key, value)) = s.split_once('=') else {
+        anyhow::bail!("Invalid hint '{}': expected KEY=VALUE", s);
+    };
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        anyhow::bail!(
+            "Invalid hint '{}': the key can't be empty or contain spaces",
+            s
+        );
+    }
+    Ok((key.to_string(), HintValue::infer(value)))
+}
+
+/// Converts a parsed hint to notify-rust's type. Hints from the spec keep
+/// their own types; others become custom hints, with booleans sent as
+/// strings since notify-rust has no custom boolean hint.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn notify_hint(key: &str, value: &HintValue) -> notify_rust::Hint {
+    let text = match value {
+        HintValue::Int(n) => n.to_string(),
+        HintValue::Bool(b) => b.to_string(),
+        HintValue::String(s) => s.clone(),
+    };
+    if let Ok(hint) = notify_rust::Hint::from_key_val(key, &text) {
+        return hint;
+    }
+    match value {
+        HintValue::Int(n) => notify_rust::Hint::CustomInt(key.to_string(), *n),
+        _ => notify_rust::Hint::Custom(key.to_string(), text),
+    }
+}
+
+/// Returns true when the backend can ask for a typed reply. Only macOS's
+/// `display dialog` has a text field; notify-rust can't read inline replies
+/// and terminal-notifier dropped its reply option.
+pub fn supports_reply(backend: Backend) -> bool {
+    matches!(backend, Backend::Auto | Backend::Osascript) && cfg!(target_os = "macos")
+}
+
+/// How long the reply dialog waits before giving up, since the hook (and
+/// Claude Code) wait on it.
+pub const REPLY_TIMEOUT_SECS: u32 = 60;
+
+/// Builds the `osascript` dialog that asks for a reply. It prints the text
+/// entered, and exits non-zero when dismissed or after `timeout_secs`.
+pub fn reply_prompt_command(title: &str, body: &str, timeout_secs: u32) -> Command {
+    let mut command = Command::new("osascript");
+    command
+        .arg("-e")
+        .arg(format!(
+            "set dialogResult to display dialog {} with title {} default answer \"\" \
+             buttons {{\"Dismiss\", \"Reply\"}} default button \"Reply\" \
+             cancel button \"Dismiss\" giving up after {}",
+            applescript_string(body),
+            applescript_string(title),
+            timeout_secs
+        ))
+        .arg("-e")
+        .arg("if gave up of dialogResult then error number -128")
+        .arg("-e")
+        .arg("text returned of dialogResult");
+    command
+}
+
+/// Shows the reply dialog and waits up to [`REPLY_TIMEOUT_SECS`] for it,
+/// returning the text entered or `None` if it was dismissed or timed out.
+pub fn prompt_reply(title: &str, body: &str) -> Result<Option<String>> {
+    let output = reply_prompt_command(title, body, REPLY_TIMEOUT_SECS)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to show reply prompt: {}", e))?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Builds the AppleScript that adds `message` to Reminders, due
+/// `due_minutes` from now when given.
+pub fn build_reminder_script(message: &str, due_minutes: Option<u32>) -> String {
+    let due = due_minutes
+        .map(|minutes| format!(", due date:((current date) + {} * minutes)", minutes))
+        .unwrap_or_default();
+    format!(
+        "tell application \"Reminders\" to make new reminder with properties {{name:{}{}}}",
+        applescript_string(message),
+        due
+    )
+}
+
+/// Adds a reminder with `osascript`. Only available on macOS.
+pub fn create_reminder(message: &str, due_minutes: Option<u32>) -> Result<()> {
+    if !cfg!(target_os = "macos") {
+        anyhow::bail!("Reminders are only supported on macOS");
+    }
+
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(build_reminder_script(message, due_minutes))
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to create reminder: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to create reminder: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Derives the identifier that groups a session's notifications into one
+/// stack, so the same session always maps to the same thread.
+pub fn thread_id_for(session_id: &str) -> String {
+    format!(
+        "claude-code-notification.{:016x}",
+        crate::stable_hash(session_id.as_bytes())
+    )
+}
+
+/// Picks the grouping key: an explicit `--group-key` as given, otherwise
+/// the one derived from the session.
+pub fn resolve_group_key(cli: Option<&str>, session_id: &str) -> String {
+    match cli.filter(|key| !key.is_empty()) {
+        Some(key) => key.to_string(),
+        None => thread_id_for(session_id),
+    }
+}
+
+/// Quotes a string as an AppleScript string literal.
+pub fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Builds the external command for command-based backends, or `None` for
+/// backends that display the notification in-process.
+///
+/// terminal-notifier has no per-notification style, so it always follows the
+/// style set for it in System Settings. `system_sound` only applies to
+/// osascript banners; `display alert` has no sound.
+fn backend_command(
+    backend: Backend,
+    style: NotificationStyle,
+    title: &str,
+    body: &str,
+    system_sound: bool,
+) -> Option<Command> {
+    match backend {
+        Backend::Auto | Backend::NotifyRust => None,
+        Backend::Osascript => {
+            let script = match style {
+                NotificationStyle::Banner => format!(
+                    "display notification {} with title {}{}",
+                    applescript_string(body),
+                    applescript_string(title),
+                    if system_sound {
+                        " sound name \"default\""
+                    } else {
+                        ""
+                    }
+                ),
+                NotificationStyle::Alert => format!(
+                    "display alert {} message {}",
+                    applescript_string(title),
+                    applescript_string(body)
+                ),
+            };
+            let mut command = Command::new("osascript");
+            command.arg("-e").arg(script);
+            Some(command)
+        }
+        Backend::TerminalNotifier => {
+            let mut command = Command::new("terminal-notifier");
+            command.arg("-title").arg(title).arg("-message").arg(body);
+            Some(command)
+        }
+    }
+}
+
+/// Builds the in-process notification with all supported attributes.
+pub fn build_notification(prepared: &PreparedNotification) -> Notification {
+    let mut notification = Notification::new();
+    notification.summary(&prepared.title).body(&prepared.body);
+    if let Some(icon) = &prepared.icon {
+        notification.icon(icon);
+    }
+    if let Some(ms) = prepared.timeout_ms {
+        notification.timeout(notify_rust::Timeout::Milliseconds(ms));
+    }
+    if prepared.system_sound {
+        notification.sound_name(SYSTEM_SOUND_NAME);
+    }
+
+    // Hints are a freedesktop concept; other platforms ignore the category
+    #[cfg(all(unix, not(target_os = "macos")))]
+    notification.hint(notify_rust::Hint::Category(prepared.category.clone()));
+    #[cfg(all(unix, not(target_os = "macos")))]
+    for (key, value) in &prepared.hints {
+        notification.hint(notify_hint(key, value));
+    }
+
+    // Freedesktop servers keep critical notifications until dismissed
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if prepared.style == NotificationStyle::Alert {
+        notification.urgency(notify_rust::Urgency::Critical);
+    }
+
+    // "default" is invoked by clicking the notification itself
+    if prepared.action_url.is_some() {
+        notification
+            .action(DEFAULT_ACTION, "Open")
+            .action(OPEN_ACTION, "Open");
+    }
+
+    notification
+}
+
+/// Checks that an action URL uses a scheme the system opener should
+/// handle: `http`, `https`, or `file`.
+pub fn validate_action_url(url: &str) -> Result<()> {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    match scheme {
+        Some(scheme)
+            if ["http", "https", "file"].contains(&scheme.to_ascii_lowercase().as_str()) =>
+        {
+            Ok(())
+        }
+        _ => anyhow::bail!(
+            "Unsupported URL '{}': expected http://, https://, or file://",
+            url
+        ),
+    }
+}
+
+/// Builds the platform's command for opening `url` in its default handler.
+pub fn open_url_command(url: &str) -> Command {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is the window title `start` expects first
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url);
+    command
+}
+
+/// Returns true when the backend can run a callback on click, which only
+/// freedesktop notification servers do.
+fn supports_actions(backend: Backend) -> bool {
+    supports_dismissal(backend)
+}
+
+/// Opens the action URL if the invoked action is one of ours.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn handle_action(prepared: &PreparedNotification, action: &str, warnings: &WarningSink) {
+    let Some(url) = &prepared.action_url else {
+        return;
+    };
+    if action == DEFAULT_ACTION || action == OPEN_ACTION {
+        if let Err(e) = open_url_command(url).spawn() {
+            warnings.warn(&format!("Failed to open '{}': {}", url, e));
+        }
+    }
+}
+
+/// Cheaply probes whether a notification server is available. On Linux/BSD
+/// this asks the freedesktop server for its information, which fails in
+/// headless CI or containers without a session bus; elsewhere notifications
+/// are assumed to work.
+pub fn notifications_supported() -> bool {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return notify_rust::get_server_information().is_ok();
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    true
+}
+
+/// Returns true when the backend reports when a notification is dismissed,
+/// which only freedesktop notification servers do.
+pub fn supports_dismissal(backend: Backend) -> bool {
+    matches!(backend, Backend::Auto | Backend::NotifyRust)
+        && cfg!(all(unix, not(target_os = "macos")))
+}
+
+/// Shows the notification in-process and blocks until it is dismissed or
+/// actioned. Where dismissal isn't reported this returns once it is shown.
+pub fn show_and_wait(prepared: &PreparedNotification, warnings: &WarningSink) -> Result<()> {
+    let handle = build_notification(prepared).show()?;
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    handle.wait_for_action(|action| handle_action(prepared, action, warnings));
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        let _ = warnings;
+        drop(handle);
+    }
+
+    Ok(())
+}
+
+/// Displays a notification using the chosen backend. Sound is handled
+/// separately by the caller.
+pub fn show_via_backend(
+    backend: Backend,
+    prepared: &PreparedNotification,
+    warnings: &WarningSink,
+) -> Result<()> {
+    let backend = backend_for_style(backend, prepared.style);
+    let Some(mut command) = backend_command(
+        backend,
+        prepared.style,
+        &prepared.title,
+        &prepared.body,
+        prepared.system_sound,
+    ) else {
+        if prepared.action_url.is_some() && supports_actions(backend) {
+            // Stay alive to handle the click
+            return show_and_wait(prepared, warnings);
+        }
+        build_notification(prepared).show()?;
+        return Ok(());
+    };
+
+    if let (Backend::TerminalNotifier, Some(url)) = (backend, &prepared.action_url) {
+        command.arg("-open").arg(url);
+    }
+    // Only terminal-notifier can group notifications; a newer notification
+    // in the same group replaces the older one
+    if let (Backend::TerminalNotifier, Some(thread_id)) = (backend, &prepared.thread_id) {
+        command.arg("-group").arg(thread_id);
+    }
+    if backend == Backend::TerminalNotifier && prepared.system_sound {
+        command.arg("-sound").arg("default");
+    }
+
+    // `display alert` is modal and blocks until dismissed, so don't wait
+    if backend == Backend::Osascript && prepared.style == NotificationStyle::Alert {
+        command.spawn().map_err(|e| {
+            anyhow::anyhow!("Failed to execute {} backend: {}", backend.as_str(), e)
+        })?;
+        return Ok(());
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to execute {} backend: {}", backend.as_str(), e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} backend failed with exit code {:?}: {}",
+            backend.as_str(),
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_backend_from_name() {
+        assert_eq!(Backend::from_name("auto"), Some(Backend::Auto));
+        assert_eq!(
+            Backend::from_name("terminal-notifier"),
+            Some(Backend::TerminalNotifier)
+        );
+        assert_eq!(Backend::from_name("growl"), None);
+    }
+
+    #[test]
+    fn test_in_process_backends_have_no_command() {
+        for style in [NotificationStyle::Banner, NotificationStyle::Alert] {
+            assert!(backend_command(Backend::Auto, style, "Title", "Body", false).is_none());
+            assert!(backend_command(Backend::NotifyRust, style, "Title", "Body", false).is_none());
+        }
+    }
+
+    #[test]
+    fn test_osascript_command() {
+        let command = backend_command(
+            Backend::Osascript,
+            NotificationStyle::Banner,
+            "Claude \"Code\"",
+            "Path C:\\tmp",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(command.get_program(), "osascript");
+        assert_eq!(
+            args(&command),
+            vec![
+                "-e",
+                r#"display notification "Path C:\\tmp" with title "Claude \"Code\"""#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_osascript_command_system_sound() {
+        let command = backend_command(
+            Backend::Osascript,
+            NotificationStyle::Banner,
+            "Claude Code",
+            "Done",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            args(&command),
+            vec![
+                "-e",
+                r#"display notification "Done" with title "Claude Code" sound name "default""#
+            ]
+        );
+
+        // Alerts have no sound to set
+        let command = backend_command(
+            Backend::Osascript,
+            NotificationStyle::Alert,
+            "Claude Code",
+            "Done",
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            args(&command),
+            vec!["-e", r#"display alert "Claude Code" message "Done""#]
+        );
+    }
+
+    #[test]
+    fn test_terminal_notifier_command() {
+        let command = backend_command(
+            Backend::TerminalNotifier,
+            NotificationStyle::Alert,
+            "Claude Code",
+            "Done",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(command.get_program(), "terminal-notifier");
+        assert_eq!(
+            args(&command),
+            vec!["-title", "Claude Code", "-message", "Done"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_group_key_explicit_wins() {
+        assert_eq!(resolve_group_key(Some("build"), "session-1"), "build");
+        assert_eq!(
+            resolve_group_key(Some("build"), "session-1"),
+            resolve_group_key(Some("build"), "session-2")
+        );
+    }
+
+    #[test]
+    fn test_resolve_group_key_falls_back_to_session() {
+        assert_eq!(
+            resolve_group_key(None, "session-1"),
+            thread_id_for("session-1")
+        );
+        assert_eq!(
+            resolve_group_key(Some(""), "session-1"),
+            thread_id_for("session-1")
+        );
+    }
+
+    #[test]
+    fn test_thread_id_for() {
+        assert_eq!(thread_id_for("session-1"), thread_id_for("session-1"));
+        assert_ne!(thread_id_for("session-1"), thread_id_for("session-2"));
+        assert!(thread_id_for("session-1").starts_with("claude-code-notification."));
+    }
+
+    #[test]
+    fn test_osascript_alert_command() {
+        let command = backend_command(
+            Backend::Osascript,
+            NotificationStyle::Alert,
+            "Claude Code",
+            "Done",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            args(&command),
+            vec!["-e", r#"display alert "Claude Code" message "Done""#]
+        );
+    }
+
+    #[test]
+    fn test_reply_prompt_command() {
+        let command = reply_prompt_command("Claude Code", "Say \"hi\"", 30);
+
+        assert_eq!(command.get_program(), "osascript");
+        assert_eq!(
+            args(&command),
+            vec![
+                "-e",
+                r#"set dialogResult to display dialog "Say \"hi\"" with title "Claude Code" default answer "" buttons {"Dismiss", "Reply"} default button "Reply" cancel button "Dismiss" giving up after 30"#,
+                "-e",
+                "if gave up of dialogResult then error number -128",
+                "-e",
+                "text returned of dialogResult",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_reminder_script() {
+        assert_eq!(
+            build_reminder_script("Review the PR", None),
+            r#"tell application "Reminders" to make new reminder with properties {name:"Review the PR"}"#
+        );
+        assert_eq!(
+            build_reminder_script("Review the PR", Some(30)),
+            r#"tell application "Reminders" to make new reminder with properties {name:"Review the PR", due date:((current date) + 30 * minutes)}"#
+        );
+    }
+
+    #[test]
+    fn test_build_reminder_script_escapes_message() {
+        assert_eq!(
+            build_reminder_script(r#"Check "C:\tmp" output"#, None),
+            r#"tell application "Reminders" to make new reminder with properties {name:"Check \"C:\\tmp\" output"}"#
+        );
+    }
+
+    #[test]
+    fn test_supports_reply() {
+        assert!(!supports_reply(Backend::TerminalNotifier));
+        assert!(!supports_reply(Backend::NotifyRust));
+        assert_eq!(supports_reply(Backend::Auto), cfg!(target_os = "macos"));
+    }
+
+    #[test]
+    fn test_backend_for_style() {
+        assert_eq!(
+            backend_for_style(Backend::Auto, NotificationStyle::Banner),
+            Backend::Auto
+        );
+        assert_eq!(
+            backend_for_style(Backend::TerminalNotifier, NotificationStyle::Alert),
+            Backend::TerminalNotifier
+        );
+
+        let auto_alert = backend_for_style(Backend::Auto, NotificationStyle::Alert);
+        if cfg!(target_os = "macos") {
+            assert_eq!(auto_alert, Backend::Osascript);
+        } else {
+            assert_eq!(auto_alert, Backend::Auto);
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_build_notification_applies_category_hint() {
+        let prepared = crate::Notifier::new()
+            .message("Done")
+            .options(crate::NotificationOptions {
+                category: Some("transfer.complete".to_string()),
+                ..Default::default()
+            })
+            .prepare();
+
+        let notification = build_notification(&prepared);
+        assert!(notification.hints.contains(&notify_rust::Hint::Category(
+            "transfer.complete".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_parse_hint_int() {
+        assert_eq!(
+            parse_hint("x=120").unwrap(),
+            ("x".to_string(), HintValue::Int(120))
+        );
+        assert_eq!(
+            parse_hint("value=-5").unwrap(),
+            ("value".to_string(), HintValue::Int(-5))
+        );
+    }
+
+    #[test]
+    fn test_parse_hint_string() {
+        assert_eq!(
+            parse_hint("desktop-entry=org.gnome.Terminal").unwrap(),
+            (
+                "desktop-entry".to_string(),
+                HintValue::String("org.gnome.Terminal".to_string())
+            )
+        );
+        assert_eq!(
+            parse_hint("x-note=a=b").unwrap().1,
+            HintValue::String("a=b".to_string())
+        );
+        assert_eq!(
+            parse_hint("empty=").unwrap().1,
+            HintValue::String(String::new())
+        );
+    }
+
+    #[test]
+    fn test_parse_hint_bool() {
+        assert_eq!(
+            parse_hint("transient=true").unwrap(),
+            ("transient".to_string(), HintValue::Bool(true))
+        );
+        assert_eq!(
+            parse_hint("resident=false").unwrap().1,
+            HintValue::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_parse_hint_malformed() {
+        assert!(parse_hint("transient").is_err());
+        assert!(parse_hint("=true").is_err());
+        assert!(parse_hint("two words=1").is_err());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_build_notification_applies_hints() {
+        let prepared = crate::Notifier::new()
+            .message("Done")
+            .options(crate::NotificationOptions {
+                hints: [
+                    "transient=true",
+                    "x-volume=3",
+                    "x-mood=calm",
+                    "x-seen=false",
+                ]
+                .iter()
+                .map(|h| parse_hint(h).unwrap())
+                .collect(),
+                ..Default::default()
+            })
+            .prepare();
+
+        let hints = build_notification(&prepared).hints;
+        assert!(hints.contains(&notify_rust::Hint::Transient(true)));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_notify_hint_custom_types() {
+        let hint = |s: &str| {
+            let (key, value) = parse_hint(s).unwrap();
+            notify_hint(&key, &value)
+        };
+
+        assert_eq!(hint("x=10"), notify_rust::Hint::X(10));
+        assert_eq!(
+            hint("x-volume=3"),
+            notify_rust::Hint::CustomInt("x-volume".into(), 3)
+        );
+        assert_eq!(
+            hint("x-mood=calm"),
+            notify_rust::Hint::Custom("x-mood".into(), "calm".into())
+        );
+        assert_eq!(
+            hint("x-seen=false"),
+            notify_rust::Hint::Custom("x-seen".into(), "false".into())
+        );
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_build_notification_system_sound() {
+        let prepared = crate::Notifier::new()
+            .message("Done")
+            .sound(crate::Sound::SystemDefault)
+            .prepare();
+
+        let notification = build_notification(&prepared);
+        assert!(notification
+            .hints
+            .contains(&notify_rust::Hint::SoundName(SYSTEM_SOUND_NAME.to_string())));
+
+        let prepared = crate::Notifier::new().message("Done").prepare();
+        assert!(!build_notification(&prepared)
+            .hints
+            .iter()
+            .any(|hint| matches!(hint, notify_rust::Hint::SoundName(_))));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_build_notification_default_category() {
+        let prepared = crate::Notifier::new().message("Done").prepare();
+
+        let notification = build_notification(&prepared);
+        assert!(notification
+            .hints
+            .contains(&notify_rust::Hint::Category(DEFAULT_CATEGORY.to_string())));
+    }
+
+    #[test]
+    fn test_validate_action_url() {
+        assert!(validate_action_url("https://github.com/org/repo/pull/1").is_ok());
+        assert!(validate_action_url("http://localhost:3000").is_ok());
+        assert!(validate_action_url("file:///tmp/report.html").is_ok());
+        assert!(validate_action_url("HTTPS://example.com").is_ok());
+
+        assert!(validate_action_url("javascript:alert(1)").is_err());
+        assert!(validate_action_url("ftp://example.com").is_err());
+        assert!(validate_action_url("example.com").is_err());
+    }
+
+    #[test]
+    fn test_open_url_command() {
+        let command = open_url_command("https://example.com");
+        let args = args(&command);
+
+        assert_eq!(args.last().unwrap(), "https://example.com");
+        if cfg!(target_os = "macos") {
+            assert_eq!(command.get_program(), "open");
+        } else if cfg!(windows) {
+            assert_eq!(command.get_program(), "cmd");
+            assert_eq!(args, vec!["/C", "start", "", "https://example.com"]);
+        } else {
+            assert_eq!(command.get_program(), "xdg-open");
+        }
+    }
+
+    #[test]
+    fn test_build_notification_adds_open_actions() {
+        let prepared = crate::Notifier::new()
+            .message("Done")
+            .options(crate::NotificationOptions {
+                action_url: Some("https://example.com".to_string()),
+                ..Default::default()
+            })
+            .prepare();
+
+        let notification = build_notification(&prepared);
+        assert!(notification.actions.contains(&OPEN_ACTION.to_string()));
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use qrcode::QrCode;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::format;
+
+/// Card width in pixels.
+const CARD_WIDTH: usize = 480;
+/// Space around the text, in pixels.
+const PADDING: usize = 16;
+/// Message lines beyond this are cut.
+const MAX_MESSAGE_LINES: usize = 12;
+/// Pixels per QR code module.
+const QR_SCALE: usize = 4;
+
+const BACKGROUND: u8 = 0xff;
+const TITLE_COLOR: u8 = 0x10;
+const MESSAGE_COLOR: u8 = 0x40;
+const RULE_COLOR: u8 = 0xc0;
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+/// Drawn for characters the font doesn't have.
+const MISSING_GLYPH: [u8; GLYPH_HEIGHT] = [0x1f, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1f];
+
+/// A 5x7 bitmap font covering uppercase ASCII, digits, and common
+/// punctuation; lowercase letters are drawn as uppercase. Each row's low
+/// five bits are its pixels, left to right.
+const FONT: &[(char, [u8; GLYPH_HEIGHT])] = &[
+    (' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('A', [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('B', [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e]),
+    ('C', [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e]),
+    ('D', [0x1e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1e]),
+    ('E', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f]),
+    ('F', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10]),
+    ('G', [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f]),
+    ('H', [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('I', [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f]),
+    ('M', [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('P', [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10]),
+    ('Q', [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d]),
+    ('R', [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11]),
+    ('S', [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e]),
+    ('T', [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a]),
+    ('X', [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x0a, 0x04, 0x04, 0x04, 0x04]),
+    ('Z', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f]),
+    ('0', [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e]),
+    ('1', [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('2', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f]),
+    ('3', [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e]),
+    ('4', [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02]),
+    ('5', [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e]),
+    ('6', [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e]),
+    ('7', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e]),
+    ('9', [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0c, 0x04, 0x08]),
+    (':', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00]),
+    (';', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x04, 0x08]),
+    ('!', [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04]),
+    ('?', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    ('_', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1f]),
+    ('\'', [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00]),
+    ('"', [0x0a, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('`', [0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    ('[', [0x0e, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0e]),
+    (']', [0x0e, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0e]),
+    ('<', [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02]),
+    ('>', [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08]),
+    ('/', [0x01, 0x01, 0x02, 0x04, 0x08, 0x10, 0x10]),
+    ('\\', [0x10, 0x10, 0x08, 0x04, 0x02, 0x01, 0x01]),
+    ('|', [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('+', [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00]),
+    ('=', [0x00, 0x00, 0x1f, 0x00, 0x1f, 0x00, 0x00]),
+    ('*', [0x00, 0x04, 0x15, 0x0e, 0x15, 0x04, 0x00]),
+    ('#', [0x0a, 0x0a, 0x1f, 0x0a, 0x1f, 0x0a, 0x0a]),
+    ('%', [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03]),
+    ('&', [0x0c, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0d]),
+    ('$', [0x04, 0x0f, 0x14, 0x0e, 0x05, 0x1e, 0x04]),
+    ('@', [0x0e, 0x11, 0x17, 0x15, 0x17, 0x10, 0x0f]),
+    ('~', [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00]),
+];
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+    FONT.iter()
+        .find(|(known, _)| *known == c)
+        .map(|(_, rows)| *rows)
+        .unwrap_or(MISSING_GLYPH)
+}
+
+/// A grayscale image being drawn on.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, shade: u8) {
+        for row in y..(y + h).min(self.height) {
+            for col in x..(x + w).min(self.width) {
+                self.pixels[row * self.width + col] = shade;
+            }
+        }
+    }
+
+    /// Draws `text` with its top-left corner at `(x, y)`, each font pixel
+    /// `scale` pixels wide.
+    fn text(&mut self, x: usize, y: usize, text: &str, scale: usize, shade: u8) {
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i * advance(scale);
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (0x10 >> col) != 0 {
+                        self.fill(left + col * scale, y + row * scale, scale, scale, shade);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Horizontal distance between characters at `scale`.
+fn advance(scale: usize) -> usize {
+    (GLYPH_WIDTH + 1) * scale
+}
+
+/// Vertical distance between lines at `scale`.
+fn line_height(scale: usize) -> usize {
+    (GLYPH_HEIGHT + 2) * scale
+}
+
+/// Renders a card with `title` above `message` and saves it as a PNG at
+/// `out`. Long titles are cut and the message is wrapped to fit.
+pub fn render_card(title: &str, message: &str, out: &Path) -> Result<()> {
+    render_card_with_link(title, message, None, out)
+}
+
+/// Like [`render_card`], adding a QR code for `link` below the message.
+pub fn render_card_with_link(
+    title: &str,
+    message: &str,
+    link: Option<&str>,
+    out: &Path,
+) -> Result<()> {
+    const TITLE_SCALE: usize = 3;
+    const MESSAGE_SCALE: usize = 2;
+    let columns = |scale| (CARD_WIDTH - 2 * PADDING) / advance(scale);
+
+    // The font has no ellipsis, so truncation leaves room for "..."
+    let title = format::truncate_at_word(title, columns(TITLE_SCALE) - 2).replace('…', "...");
+    let wrapped = format::wrap_body(message, columns(MESSAGE_SCALE));
+    let lines: Vec<&str> = wrapped.lines().take(MAX_MESSAGE_LINES).collect();
+    let qr = link
+        .map(QrCode::new)
+        .transpose()
+        .context("Link is too long for a QR code")?;
+
+    let rule_y = PADDING + line_height(TITLE_SCALE);
+    let qr_y = rule_y + PADDING + lines.len() * line_height(MESSAGE_SCALE);
+    let qr_size = qr.as_ref().map_or(0, |qr| qr.width() * QR_SCALE + PADDING);
+    let mut canvas = Canvas::new(CARD_WIDTH, qr_y + qr_size + PADDING);
+
+    canvas.text(PADDING, PADDING, &title, TITLE_SCALE, TITLE_COLOR);
+    canvas.fill(PADDING, rule_y, CARD_WIDTH - 2 * PADDING, 2, RULE_COLOR);
+    for (i, line) in lines.iter().enumerate() {
+        let y = rule_y + PADDING + i * line_height(MESSAGE_SCALE);
+        canvas.text(PADDING, y, line, MESSAGE_SCALE, MESSAGE_COLOR);
+    }
+    if let Some(qr) = &qr {
+        // The card's padding doubles as the QR code's quiet zone
+        for (i, color) in qr.to_colors().into_iter().enumerate() {
+            if color == qrcode::Color::Dark {
+                let (row, col) = (i / qr.width(), i % qr.width());
+                canvas.fill(
+                    PADDING + col * QR_SCALE,
+                    qr_y + PADDING + row * QR_SCALE,
+                    QR_SCALE,
+                    QR_SCALE,
+                    TITLE_COLOR,
+                );
+            }
+        }
+    }
+
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_png(&canvas, out).with_context(|| format!("Failed to save card to {}", out.display()))
+}
+
+/// Renders a card into `dir` named after the notification `id`, returning
+/// its path.
+pub fn save_card(
+    dir: &Path,
+    id: &str,
+    title: &str,
+    message: &str,
+    link: Option<&str>,
+) -> Result<PathBuf> {
+    let path = dir.join(format!("{}.png", id));
+    render_card_with_link(title, message, link, &path)?;
+    Ok(path)
+}
+
+/// The URL a card's QR code points at: the transcript's file name under
+/// `base`, where the transcripts are served. `None` without a transcript.
+pub fn transcript_link(base: &str, transcript_path: &str) -> Option<String> {
+    let name = Path::new(transcript_path).file_name()?.to_str()?;
+    Some(format!("{}/{}", base.trim_end_matches('/'), name))
+}
+
+/// Writes the canvas as an 8-bit grayscale PNG.
+fn write_png(canvas: &Canvas, out: &Path) -> Result<()> {
+    let file = BufWriter::new(fs::File::create(out)?);
+    let mut encoder = png::Encoder::new(file, canvas.width as u32, canvas.height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&canvas.pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_card_creates_png() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let out = temp_dir.path().join("cards").join("card.png");
+
+        render_card("Claude Code", "Build finished: 42 tests passed", &out).unwrap();
+
+        let bytes = fs::read(&out).unwrap();
+        assert!(bytes.len() > 100);
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    fn png_height(path: &Path) -> u32 {
+        let decoder = png::Decoder::new(std::io::BufReader::new(fs::File::open(path).unwrap()));
+        decoder.read_info().unwrap().info().height
+    }
+
+    #[test]
+    fn test_render_card_with_qr_code() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let plain = temp_dir.path().join("plain.png");
+        let linked = temp_dir.path().join("linked.png");
+
+        render_card("Build", "Done", &plain).unwrap();
+        render_card_with_link(
+            "Build",
+            "Done",
+            Some("https://example.com/transcripts/abc.jsonl"),
+            &linked,
+        )
+        .unwrap();
+
+        assert!(png_height(&linked) > png_height(&plain) + 100);
+    }
+
+    #[test]
+    fn test_transcript_link() {
+        assert_eq!(
+            transcript_link(
+                "https://example.com/t/",
+                "/home/me/.claude/projects/x/abc.jsonl"
+            ),
+            Some("https://example.com/t/abc.jsonl".to_string())
+        );
+        assert_eq!(transcript_link("https://example.com/t", ""), None);
+    }
+
+    #[test]
+    fn test_save_card_names_by_id() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+
+        let path = save_card(temp_dir.path(), "abc-123", "Build", "Done", None).unwrap();
+
+        assert_eq!(path, temp_dir.path().join("abc-123.png"));
+        assert!(fs::metadata(&path).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn test_glyph_lookup() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('é'), MISSING_GLYPH);
+    }
+}
@@ -0,0 +1,167 @@
+/// Keywords that mark a message as reporting a failure.
+const ERROR_KEYWORDS: &[&str] = &["error", "failed", "failure", "fatal", "panic", "exception"];
+
+/// Keywords that mark a message as needing attention without failing.
+const WARN_KEYWORDS: &[&str] = &[
+    "warning",
+    "warn",
+    "deprecated",
+    "needs",
+    "waiting",
+    "permission",
+];
+
+/// Keywords that mark a message as reporting a completed task.
+const SUCCESS_KEYWORDS: &[&str] = &[
+    "finished",
+    "complete",
+    "completed",
+    "done",
+    "passed",
+    "success",
+    "succeeded",
+];
+
+/// Returns true when the message contains any of the failure keywords
+/// (case-insensitive).
+pub fn is_error_message(message: &str) -> bool {
+    let lowered = message.to_lowercase();
+    ERROR_KEYWORDS
+        .iter()
+        .any(|keyword| lowered.contains(keyword))
+}
+
+/// Returns true when the message contains any of the success keywords
+/// (case-insensitive).
+pub fn is_success_message(message: &str) -> bool {
+    let lowered = message.to_lowercase();
+    SUCCESS_KEYWORDS
+        .iter()
+        .any(|keyword| lowered.contains(keyword))
+}
+
+/// How important a message is, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "info" => Some(Severity::Info),
+            "warn" => Some(Severity::Warn),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Classifies a message by keyword. Failure keywords win over warning
+/// keywords; anything else is informational.
+pub fn classify_severity(message: &str) -> Severity {
+    if is_error_message(message) {
+        return Severity::Error;
+    }
+
+    let lowered = message.to_lowercase();
+    if WARN_KEYWORDS
+        .iter()
+        .any(|keyword| lowered.contains(keyword))
+    {
+        Severity::Warn
+    } else {
+        Severity::Info
+    }
+}
+
+/// Returns true when a message of severity `sev` should be shown given the
+/// `min` threshold.
+pub fn passes_filter(sev: Severity, min: Severity) -> bool {
+    sev >= min
+}
+
+/// Picks a freedesktop icon name for the message. Failures take precedence
+/// over success keywords, so "Build finished with errors" is an error.
+pub fn icon_for_message(message: &str) -> &'static str {
+    if is_error_message(message) {
+        "dialog-error"
+    } else if is_success_message(message) {
+        "emblem-default"
+    } else {
+        "dialog-information"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_error_message() {
+        assert!(is_error_message("Build failed with 3 errors"));
+        assert!(is_error_message("FATAL: out of memory"));
+        assert!(is_error_message("thread 'main' panicked"));
+        assert!(!is_error_message("Claude is waiting for your input"));
+        assert!(!is_error_message(""));
+    }
+
+    #[test]
+    fn test_icon_for_message() {
+        assert_eq!(icon_for_message("Build failed"), "dialog-error");
+        assert_eq!(
+            icon_for_message("Build finished with errors"),
+            "dialog-error"
+        );
+        assert_eq!(icon_for_message("Claude finished"), "emblem-default");
+        assert_eq!(icon_for_message("All tests passed"), "emblem-default");
+        assert_eq!(icon_for_message("Claude needs input"), "dialog-information");
+        assert_eq!(icon_for_message(""), "dialog-information");
+    }
+
+    #[test]
+    fn test_classify_severity() {
+        assert_eq!(classify_severity("Build failed"), Severity::Error);
+        assert_eq!(
+            classify_severity("Claude needs your permission to use Bash"),
+            Severity::Warn
+        );
+        assert_eq!(
+            classify_severity("Warning: 2 tests skipped"),
+            Severity::Warn
+        );
+        assert_eq!(classify_severity("Warning: build failed"), Severity::Error);
+        assert_eq!(classify_severity("Claude finished"), Severity::Info);
+    }
+
+    #[test]
+    fn test_passes_filter() {
+        use Severity::*;
+
+        let cases = [
+            (Info, Info, true),
+            (Info, Warn, false),
+            (Info, Error, false),
+            (Warn, Info, true),
+            (Warn, Warn, true),
+            (Warn, Error, false),
+            (Error, Info, true),
+            (Error, Warn, true),
+            (Error, Error, true),
+        ];
+        for (sev, min, expected) in cases {
+            assert_eq!(passes_filter(sev, min), expected, "{:?} >= {:?}", sev, min);
+        }
+    }
+}
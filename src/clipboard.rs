@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Builds the commands that can copy to the clipboard on `os` (as in
+/// `std::env::consts::OS`), in the order they should be tried. Wayland
+/// sessions prefer `wl-copy` over `xclip`.
+pub fn clipboard_commands(os: &str, wayland: bool) -> Vec<Command> {
+    let command = |program: &str, args: &[&str]| {
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    };
+
+    match os {
+        "macos" => vec![command("pbcopy", &[])],
+        "windows" => vec![command("clip", &[])],
+        _ => {
+            let xclip = command("xclip", &["-selection", "clipboard"]);
+            if wayland {
+                vec![command("wl-copy", &[]), xclip]
+            } else {
+                vec![xclip]
+            }
+        }
+    }
+}
+
+/// Copies `text` to the system clipboard with the first clipboard tool that
+/// is installed.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let mut tried = Vec::new();
+
+    for mut command in clipboard_commands(std::env::consts::OS, wayland) {
+        let program = command.get_program().to_string_lossy().to_string();
+        let mut child = match command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tried.push(program);
+                continue;
+            }
+            Err(e) => anyhow::bail!("Failed to run {}: {}", program, e),
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            anyhow::bail!("{} failed with exit code {:?}", program, status.code());
+        }
+        return Ok(());
+    }
+
+    anyhow::bail!("No clipboard tool found (tried {})", tried.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(commands: &[Command]) -> Vec<String> {
+        commands
+            .iter()
+            .map(|command| {
+                std::iter::once(command.get_program())
+                    .chain(command.get_args())
+                    .map(|part| part.to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clipboard_commands_macos() {
+        assert_eq!(describe(&clipboard_commands("macos", false)), ["pbcopy"]);
+    }
+
+    #[test]
+    fn test_clipboard_commands_linux() {
+        assert_eq!(
+            describe(&clipboard_commands("linux", false)),
+            ["xclip -selection clipboard"]
+        );
+        assert_eq!(
+            describe(&clipboard_commands("linux", true)),
+            ["wl-copy", "xclip -selection clipboard"]
+        );
+    }
+
+    #[test]
+    fn test_clipboard_commands_windows() {
+        assert_eq!(describe(&clipboard_commands("windows", false)), ["clip"]);
+    }
+}
@@ -0,0 +1,1025 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::embedded::{self, EmbeddedSound};
+use crate::push::PushCredentials;
+use crate::quiet::{self, QuietHours, QuietMode};
+use crate::sessions::SessionSounds;
+use crate::{
+    paths, EventSounds, Sound, SoundPolicy, VolumeTable, DEFAULT_PLAYER, DEFAULT_TITLE,
+    SYSTEM_SOUNDS_DIR,
+};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// System-wide config file, read before the per-user one.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/claude-code-notification/config.toml";
+
+/// Settings that can be provided by the config file, environment variables,
+/// or CLI flags. Unset fields fall through to the next layer.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub sound: Option<String>,
+    /// A base64 sound kept in the config file, used when `sound` isn't set.
+    pub custom_sound: Option<EmbeddedSound>,
+    pub sound_on: Option<SoundPolicy>,
+    pub title: Option<String>,
+    /// Command used to play sounds instead of `afplay`.
+    pub player: Option<String>,
+    /// Directory that bare sound names resolve against.
+    pub sound_dir: Option<PathBuf>,
+    /// How long notifications stay up in milliseconds, where supported.
+    pub timeout_ms: Option<u32>,
+    /// Per-sound volume multipliers (the `[volumes]` table).
+    pub volumes: VolumeTable,
+    /// Sounds by session id pattern (the `[sessions]` table).
+    pub sessions: SessionSounds,
+    /// Sounds by hook event name (the `[event_sounds]` table).
+    pub event_sounds: EventSounds,
+    /// Start of the daily quiet-hours window (`HH:MM`, local time).
+    #[serde(deserialize_with = "quiet::deserialize_time")]
+    pub quiet_start: Option<chrono::NaiveTime>,
+    /// End of the quiet-hours window; may be earlier than the start to
+    /// cross midnight.
+    #[serde(deserialize_with = "quiet::deserialize_time")]
+    pub quiet_end: Option<chrono::NaiveTime>,
+    /// Whether quiet hours silence or suppress notifications.
+    pub quiet_mode: Option<QuietMode>,
+    /// Named overrides selected with `--profile` (the `[profiles.*]` tables).
+    pub profiles: HashMap<String, Profile>,
+    /// Topics and tokens for `--push` (the `[push]` table).
+    pub push: PushCredentials,
+}
+
+/// A named set of overrides applied over the base config file values.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub sound: Option<String>,
+    pub sound_on: Option<SoundPolicy>,
+    pub title: Option<String>,
+    pub player: Option<String>,
+    pub sound_dir: Option<PathBuf>,
+    pub timeout_ms: Option<u32>,
+}
+
+impl Config {
+    /// Builds a config layer from `CLAUDE_NOTIFICATION_*` environment
+    /// variables using the provided lookup function.
+    pub fn from_env<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        Config {
+            sound: lookup("CLAUDE_NOTIFICATION_SOUND").filter(|v| !v.is_empty()),
+            custom_sound: None,
+            sound_on: lookup("CLAUDE_NOTIFICATION_SOUND_ON")
+                .and_then(|v| SoundPolicy::from_name(&v)),
+            title: None,
+            player: lookup("CLAUDE_NOTIFICATION_AFPLAY").filter(|v| !v.is_empty()),
+            sound_dir: None,
+            timeout_ms: None,
+            volumes: VolumeTable::new(),
+            sessions: SessionSounds::default(),
+            event_sounds: EventSounds::new(),
+            quiet_start: None,
+            quiet_end: None,
+            quiet_mode: None,
+            profiles: HashMap::new(),
+            push: PushCredentials::from_env(&lookup),
+        }
+    }
+}
+
+/// The fully-resolved configuration after all layers are merged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub sound: String,
+    pub sound_on: SoundPolicy,
+    pub title: String,
+    pub player: String,
+    pub sound_dir: PathBuf,
+    /// Unset leaves the timeout to the notification daemon.
+    pub timeout_ms: Option<u32>,
+    pub volumes: VolumeTable,
+    pub sessions: SessionSounds,
+    pub event_sounds: EventSounds,
+    /// Set when both `quiet_start` and `quiet_end` are configured.
+    pub quiet_hours: Option<QuietHours>,
+    /// Push credentials, with environment variables over the config file.
+    pub push: PushCredentials,
+}
+
+/// Where a resolved setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    ConfigFile,
+    Env,
+    Cli,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Source::Default => "default",
+            Source::ConfigFile => "config file",
+            Source::Env => "environment",
+            Source::Cli => "command line",
+        }
+    }
+}
+
+/// Returns the default config file location, honoring `XDG_CONFIG_HOME`.
+pub fn default_config_path() -> Option<PathBuf> {
+    Some(paths::config_dir()?.join(CONFIG_FILE_NAME))
+}
+
+/// Loads the config file at `path`, returning `None` if it doesn't exist.
+pub fn load_config(path: &Path) -> Result<Option<Config>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let config = parse_config_str(&content)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+    Ok(Some(config))
+}
+
+/// Loads the config file given with `--config`. Unlike [`load_config`], a
+/// missing file is an error since the user asked for it explicitly.
+pub fn load_explicit_config(path: &Path) -> Result<Config> {
+    if !path.exists() {
+        anyhow::bail!("Config file {} does not exist", path.display());
+    }
+    Ok(load_config(path)?.unwrap_or_default())
+}
+
+/// Loads the system-wide config at `system`, then the per-user config at
+/// `user`, and merges them with the user's values winning. Returns `None`
+/// when neither file exists.
+pub fn load_layered_config(system: &Path, user: Option<&Path>) -> Result<Option<Config>> {
+    let system = load_config(system)?;
+    let user = match user {
+        Some(path) => load_config(path)?,
+        None => None,
+    };
+
+    Ok(match (system, user) {
+        (Some(system), Some(user)) => Some(merge_configs(system, user)),
+        (system, user) => user.or(system),
+    })
+}
+
+/// Layers `over` on top of `base`. Values set in `over` replace those in
+/// `base`, and tables are merged key by key. A sound or `[custom_sound]` in
+/// `over` replaces both in `base`, since either one picks the sound.
+pub fn merge_configs(base: Config, over: Config) -> Config {
+    let (sound, custom_sound) = if over.sound.is_some() || over.custom_sound.is_some() {
+        (over.sound, over.custom_sound)
+    } else {
+        (base.sound, base.custom_sound)
+    };
+
+    let mut volumes = base.volumes;
+    volumes.extend(over.volumes);
+    let mut event_sounds = base.event_sounds;
+    event_sounds.extend(over.event_sounds);
+
+    // The first matching pattern wins, so `over`'s patterns go first
+    let mut sessions = over.sessions.0;
+    for (pattern, sound) in base.sessions.0 {
+        if !sessions.iter().any(|(existing, _)| *existing == pattern) {
+            sessions.push((pattern, sound));
+        }
+    }
+
+    let mut profiles = base.profiles;
+    for (name, over) in over.profiles {
+        let merged = match profiles.remove(&name) {
+            Some(base) => Profile {
+                sound: over.sound.or(base.sound),
+                sound_on: over.sound_on.or(base.sound_on),
+                title: over.title.or(base.title),
+                player: over.player.or(base.player),
+                sound_dir: over.sound_dir.or(base.sound_dir),
+                timeout_ms: over.timeout_ms.or(base.timeout_ms),
+            },
+            None => over,
+        };
+        profiles.insert(name, merged);
+    }
+
+    Config {
+        sound,
+        custom_sound,
+        sound_on: over.sound_on.or(base.sound_on),
+        title: over.title.or(base.title),
+        player: over.player.or(base.player),
+        sound_dir: over.sound_dir.or(base.sound_dir),
+        timeout_ms: over.timeout_ms.or(base.timeout_ms),
+        volumes,
+        sessions: SessionSounds(sessions),
+        event_sounds,
+        quiet_start: over.quiet_start.or(base.quiet_start),
+        quiet_end: over.quiet_end.or(base.quiet_end),
+        quiet_mode: over.quiet_mode.or(base.quiet_mode),
+        profiles,
+        push: base.push.merge(&over.push),
+    }
+}
+
+/// Applies the named profile's values over the base config file values. No
+/// profile leaves the config as is; an unknown name is an error.
+pub fn resolve_profile(config: &Config, name: Option<&str>) -> Result<Config> {
+    let Some(name) = name else {
+        return Ok(config.clone());
+    };
+    let Some(profile) = config.profiles.get(name) else {
+        let mut known: Vec<&String> = config.profiles.keys().collect();
+        known.sort();
+        anyhow::bail!(
+            "Unknown profile '{}' (available: {})",
+            name,
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        );
+    };
+
+    let profile = profile.clone();
+    Ok(Config {
+        sound: profile.sound.or_else(|| config.sound.clone()),
+        sound_on: profile.sound_on.or(config.sound_on),
+        title: profile.title.or_else(|| config.title.clone()),
+        player: profile.player.or_else(|| config.player.clone()),
+        sound_dir: profile.sound_dir.or_else(|| config.sound_dir.clone()),
+        timeout_ms: profile.timeout_ms.or(config.timeout_ms),
+        ..config.clone()
+    })
+}
+
+/// Decodes the config's `[custom_sound]` into `cache_dir` and uses its path
+/// as the config's sound. An explicit `sound` takes precedence.
+pub fn apply_custom_sound(mut config: Config, cache_dir: &Path) -> Result<Config> {
+    if let Some(custom) = config.custom_sound.take() {
+        if config.sound.is_none() {
+            let path =
+                embedded::materialize_embedded_sound(&custom.data, &custom.extension, cache_dir)
+                    .context("Failed to load [custom_sound]")?;
+            config.sound = Some(path.display().to_string());
+        }
+    }
+    Ok(config)
+}
+
+/// Parses TOML config text, reporting the line and column of any error.
+pub fn parse_config_str(s: &str) -> Result<Config> {
+    toml::from_str(s).map_err(|e| {
+        let Some(span) = e.span() else {
+            return anyhow::anyhow!("{}", e.message());
+        };
+
+        let before = &s[..span.start];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        anyhow::anyhow!("line {}, column {}: {}", line, column, e.message())
+    })
+}
+
+/// Picks the value from the highest-precedence layer that sets it and
+/// records which layer that was.
+fn pick<T: Clone>(
+    key: &str,
+    default: T,
+    layers: &[(Source, Option<&T>)],
+    sources: &mut HashMap<String, Source>,
+) -> T {
+    let (source, value) = layers
+        .iter()
+        .rev()
+        .find_map(|(source, value)| value.map(|v| (*source, v.clone())))
+        .unwrap_or((Source::Default, default));
+
+    sources.insert(key.to_string(), source);
+    value
+}
+
+/// Merges defaults, the config file, environment variables, and CLI flags
+/// (in increasing order of precedence).
+pub fn resolve_config(
+    file: Option<&Config>,
+    env: &Config,
+    cli: &Config,
+) -> (Settings, HashMap<String, Source>) {
+    let file = file.cloned().unwrap_or_default();
+    let mut sources = HashMap::new();
+
+    let sound = pick(
+        "sound",
+        Sound::default().as_str().to_string(),
+        &[
+            (Source::ConfigFile, file.sound.as_ref()),
+            (Source::Env, env.sound.as_ref()),
+            (Source::Cli, cli.sound.as_ref()),
+        ],
+        &mut sources,
+    );
+
+    let sound_on = pick(
+        "sound_on",
+        SoundPolicy::default(),
+        &[
+            (Source::ConfigFile, file.sound_on.as_ref()),
+            (Source::Env, env.sound_on.as_ref()),
+            (Source::Cli, cli.sound_on.as_ref()),
+        ],
+        &mut sources,
+    );
+
+    let title = pick(
+        "title",
+        DEFAULT_TITLE.to_string(),
+        &[
+            (Source::ConfigFile, file.title.as_ref()),
+            (Source::Env, env.title.as_ref()),
+            (Source::Cli, cli.title.as_ref()),
+        ],
+        &mut sources,
+    );
+
+    let player = pick(
+        "player",
+        DEFAULT_PLAYER.to_string(),
+        &[
+            (Source::ConfigFile, file.player.as_ref()),
+            (Source::Env, env.player.as_ref()),
+            (Source::Cli, cli.player.as_ref()),
+        ],
+        &mut sources,
+    );
+
+    let sound_dir = pick(
+        "sound_dir",
+        PathBuf::from(SYSTEM_SOUNDS_DIR),
+        &[
+            (Source::ConfigFile, file.sound_dir.as_ref()),
+            (Source::Env, env.sound_dir.as_ref()),
+            (Source::Cli, cli.sound_dir.as_ref()),
+        ],
+        &mut sources,
+    );
+
+    let timeout_ms = pick(
+        "timeout_ms",
+        None,
+        &[
+            (
+                Source::ConfigFile,
+                Some(&file.timeout_ms).filter(|t| t.is_some()),
+            ),
+            (Source::Env, Some(&env.timeout_ms).filter(|t| t.is_some())),
+            (Source::Cli, Some(&cli.timeout_ms).filter(|t| t.is_some())),
+        ],
+        &mut sources,
+    );
+
+    // Tables only come from the config file
+    let volumes = pick(
+        "volumes",
+        VolumeTable::new(),
+        &[(
+            Source::ConfigFile,
+            Some(&file.volumes).filter(|v| !v.is_empty()),
+        )],
+        &mut sources,
+    );
+
+    let sessions = pick(
+        "sessions",
+        SessionSounds::default(),
+        &[(
+            Source::ConfigFile,
+            Some(&file.sessions).filter(|s| !s.is_empty()),
+        )],
+        &mut sources,
+    );
+
+    let event_sounds = pick(
+        "event_sounds",
+        EventSounds::new(),
+        &[(
+            Source::ConfigFile,
+            Some(&file.event_sounds).filter(|e| !e.is_empty()),
+        )],
+        &mut sources,
+    );
+
+    // Quiet hours only come from the config file, and need both ends
+    let quiet_hours = match (file.quiet_start, file.quiet_end) {
+        (Some(start), Some(end)) => {
+            sources.insert("quiet_hours".to_string(), Source::ConfigFile);
+            Some(QuietHours {
+                start,
+                end,
+                mode: file.quiet_mode.unwrap_or_default(),
+            })
+        }
+        _ => None,
+    };
+
+    (
+        Settings {
+            sound,
+            sound_on,
+            title,
+            player,
+            sound_dir,
+            timeout_ms,
+            volumes,
+            sessions,
+            event_sounds,
+            quiet_hours,
+            push: file.push.merge(&env.push),
+        },
+        sources,
+    )
+}
+
+/// Renders the resolved settings, annotating each value with its source.
+pub fn describe(settings: &Settings, sources: &HashMap<String, Source>) -> String {
+    let source_of = |key: &str| sources.get(key).copied().unwrap_or(Source::Default);
+
+    [
+        format!(
+            "sound = {:?}  # {}",
+            settings.sound,
+            source_of("sound").as_str()
+        ),
+        format!(
+            "sound_on = {:?}  # {}",
+            settings.sound_on.as_str(),
+            source_of("sound_on").as_str()
+        ),
+        format!(
+            "title = {:?}  # {}",
+            settings.title,
+            source_of("title").as_str()
+        ),
+        format!(
+            "player = {:?}  # {}",
+            settings.player,
+            source_of("player").as_str()
+        ),
+        format!(
+            "sound_dir = {:?}  # {}",
+            settings.sound_dir.display().to_string(),
+            source_of("sound_dir").as_str()
+        ),
+    ]
+    .into_iter()
+    .chain(settings.timeout_ms.map(|timeout| {
+        format!(
+            "timeout_ms = {}  # {}",
+            timeout,
+            source_of("timeout_ms").as_str()
+        )
+    }))
+    .chain(describe_table(
+        "volumes",
+        &settings.volumes,
+        source_of("volumes"),
+    ))
+    .chain(describe_quiet_hours(
+        settings.quiet_hours,
+        source_of("quiet_hours"),
+    ))
+    .chain(describe_sessions(&settings.sessions, source_of("sessions")))
+    .chain(describe_table(
+        "event_sounds",
+        &settings.event_sounds,
+        source_of("event_sounds"),
+    ))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Renders the quiet-hours keys, or nothing if quiet hours are off.
+fn describe_quiet_hours(quiet_hours: Option<QuietHours>, source: Source) -> Vec<String> {
+    let Some(q) = quiet_hours else {
+        return Vec::new();
+    };
+
+    vec![
+        format!(
+            "quiet_start = \"{}\"  # {}",
+            q.start.format("%H:%M"),
+            source.as_str()
+        ),
+        format!(
+            "quiet_end = \"{}\"  # {}",
+            q.end.format("%H:%M"),
+            source.as_str()
+        ),
+        format!("quiet_mode = {:?}  # {}", q.mode.as_str(), source.as_str()),
+    ]
+}
+
+/// Renders the `[sessions]` table in match order, or nothing if it's empty.
+fn describe_sessions(sessions: &SessionSounds, source: Source) -> Vec<String> {
+    if sessions.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec![format!("[sessions]  # {}", source.as_str())];
+    lines.extend(
+        sessions
+            .0
+            .iter()
+            .map(|(pattern, sound)| format!("{:?} = {:?}", pattern, sound)),
+    );
+    lines
+}
+
+/// Renders a `[name]` table with sorted keys, or nothing if it's empty.
+fn describe_table<V: std::fmt::Debug>(
+    name: &str,
+    table: &HashMap<String, V>,
+    source: Source,
+) -> Vec<String> {
+    if table.is_empty() {
+        return Vec::new();
+    }
+
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+
+    let mut lines = vec![format!("[{}]  # {}", name, source.as_str())];
+    lines.extend(
+        keys.iter()
+            .map(|key| format!("{} = {:?}", key, table[*key])),
+    );
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_configs_user_overrides_system() {
+        let system = parse_config_str(
+            "sound = \"Submarine\"\ntitle = \"Shared\"\nplayer = \"paplay\"\n\n[volumes]\nGlass = 0.5\nHero = 0.8\n",
+        )
+        .unwrap();
+        let user = parse_config_str("sound = \"Frog\"\n\n[volumes]\nHero = 1.0\n").unwrap();
+
+        let merged = merge_configs(system, user);
+
+        assert_eq!(merged.sound.as_deref(), Some("Frog"));
+        assert_eq!(merged.title.as_deref(), Some("Shared"));
+        assert_eq!(merged.player.as_deref(), Some("paplay"));
+        assert_eq!(merged.volumes["Glass"], 0.5);
+        assert_eq!(merged.volumes["Hero"], 1.0);
+    }
+
+    #[test]
+    fn test_merge_configs_session_maps_are_additive() {
+        let system =
+            parse_config_str("[sessions]\n\"ci-*\" = \"Basso\"\n\"*\" = \"Glass\"\n").unwrap();
+        let user =
+            parse_config_str("[sessions]\n\"work-*\" = \"Hero\"\n\"ci-*\" = \"Ping\"\n").unwrap();
+
+        let merged = merge_configs(system, user);
+
+        assert_eq!(
+            merged.sessions.0,
+            vec![
+                ("work-*".to_string(), "Hero".to_string()),
+                ("ci-*".to_string(), "Ping".to_string()),
+                ("*".to_string(), "Glass".to_string()),
+            ]
+        );
+        assert_eq!(merged.sessions.sound_for("ci-42"), Some("Ping"));
+        assert_eq!(merged.sessions.sound_for("home"), Some("Glass"));
+    }
+
+    #[test]
+    fn test_merge_configs_user_sound_replaces_custom_sound() {
+        let system = Config {
+            custom_sound: Some(EmbeddedSound {
+                data: "UklGRg==".to_string(),
+                extension: "wav".to_string(),
+            }),
+            ..Default::default()
+        };
+        let user = Config {
+            sound: Some("Hero".to_string()),
+            ..Default::default()
+        };
+
+        let merged = merge_configs(system.clone(), user);
+        assert_eq!(merged.sound.as_deref(), Some("Hero"));
+        assert_eq!(merged.custom_sound, None);
+
+        assert_eq!(merge_configs(system.clone(), Config::default()), system);
+    }
+
+    #[test]
+    fn test_load_layered_config() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let system = temp_dir.path().join("system.toml");
+        let user = temp_dir.path().join("user.toml");
+
+        assert_eq!(load_layered_config(&system, Some(&user)).unwrap(), None);
+
+        fs::write(&system, "title = \"Shared\"\nsound = \"Basso\"\n").unwrap();
+        let config = load_layered_config(&system, Some(&user)).unwrap().unwrap();
+        assert_eq!(config.sound.as_deref(), Some("Basso"));
+
+        fs::write(&user, "sound = \"Hero\"\n").unwrap();
+        let config = load_layered_config(&system, Some(&user)).unwrap().unwrap();
+        assert_eq!(config.sound.as_deref(), Some("Hero"));
+        assert_eq!(config.title.as_deref(), Some("Shared"));
+    }
+
+    #[test]
+    fn test_resolve_config_defaults() {
+        let (settings, sources) = resolve_config(None, &Config::default(), &Config::default());
+
+        assert_eq!(settings.sound, "Glass");
+        assert_eq!(settings.sound_on, SoundPolicy::Always);
+        assert_eq!(sources["sound"], Source::Default);
+        assert_eq!(sources["sound_on"], Source::Default);
+    }
+
+    #[test]
+    fn test_resolve_config_reports_overridden_source() {
+        let file = Config {
+            sound: Some("Submarine".to_string()),
+            sound_on: Some(SoundPolicy::Never),
+            title: Some("Work".to_string()),
+            ..Default::default()
+        };
+        let env = Config {
+            sound_on: Some(SoundPolicy::Error),
+            ..Default::default()
+        };
+        let cli = Config {
+            sound: Some("Frog".to_string()),
+            ..Default::default()
+        };
+
+        let (settings, sources) = resolve_config(Some(&file), &env, &cli);
+
+        assert_eq!(settings.sound, "Frog");
+        assert_eq!(sources["sound"], Source::Cli);
+        assert_eq!(settings.sound_on, SoundPolicy::Error);
+        assert_eq!(sources["sound_on"], Source::Env);
+        assert_eq!(settings.title, "Work");
+        assert_eq!(sources["title"], Source::ConfigFile);
+    }
+
+    #[test]
+    fn test_config_from_env() {
+        let config = Config::from_env(|key| match key {
+            "CLAUDE_NOTIFICATION_SOUND_ON" => Some("error".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config.sound_on, Some(SoundPolicy::Error));
+        assert_eq!(config.sound, None);
+    }
+
+    fn sound_env(key: &str) -> Option<String> {
+        match key {
+            "CLAUDE_NOTIFICATION_SOUND" => Some("Submarine".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_sound_env_used_without_flag() {
+        let file = Config {
+            sound: Some("Hero".to_string()),
+            ..Default::default()
+        };
+        let env = Config::from_env(sound_env);
+
+        let (settings, sources) = resolve_config(Some(&file), &env, &Config::default());
+        assert_eq!(settings.sound, "Submarine");
+        assert_eq!(sources["sound"], Source::Env);
+    }
+
+    #[test]
+    fn test_sound_flag_wins_over_env() {
+        let env = Config::from_env(sound_env);
+        let cli = Config {
+            sound: Some("Frog".to_string()),
+            ..Default::default()
+        };
+
+        let (settings, sources) = resolve_config(None, &env, &cli);
+        assert_eq!(settings.sound, "Frog");
+        assert_eq!(sources["sound"], Source::Cli);
+    }
+
+    #[test]
+    fn test_player_from_env_and_flag() {
+        let env = Config::from_env(|key| match key {
+            "CLAUDE_NOTIFICATION_AFPLAY" => Some("/opt/bin/afplay".to_string()),
+            _ => None,
+        });
+
+        let (settings, sources) = resolve_config(None, &env, &Config::default());
+        assert_eq!(settings.player, "/opt/bin/afplay");
+        assert_eq!(sources["player"], Source::Env);
+
+        let cli = Config {
+            player: Some("play-wrapper".to_string()),
+            ..Default::default()
+        };
+        let (settings, _) = resolve_config(None, &env, &cli);
+        assert_eq!(settings.player, "play-wrapper");
+
+        let (settings, _) = resolve_config(None, &Config::default(), &Config::default());
+        assert_eq!(settings.player, "afplay");
+    }
+
+    #[test]
+    fn test_sound_dir_from_config_file() {
+        let file = parse_config_str("sound_dir = \"/opt/sounds\"\n").unwrap();
+        let (settings, sources) =
+            resolve_config(Some(&file), &Config::default(), &Config::default());
+
+        assert_eq!(settings.sound_dir, PathBuf::from("/opt/sounds"));
+        assert_eq!(sources["sound_dir"], Source::ConfigFile);
+    }
+
+    #[test]
+    fn test_sessions_table_keeps_file_order() {
+        let file =
+            parse_config_str("[sessions]\n\"feat-*\" = \"Hero\"\n\"*\" = \"Tink\"\n").unwrap();
+        let (settings, sources) =
+            resolve_config(Some(&file), &Config::default(), &Config::default());
+
+        assert_eq!(
+            settings.sessions.0,
+            vec![
+                ("feat-*".to_string(), "Hero".to_string()),
+                ("*".to_string(), "Tink".to_string())
+            ]
+        );
+        assert_eq!(sources["sessions"], Source::ConfigFile);
+        assert!(describe(&settings, &sources)
+            .contains("[sessions]  # config file\n\"feat-*\" = \"Hero\""));
+    }
+
+    #[test]
+    fn test_event_sounds_from_config_file() {
+        let file =
+            parse_config_str("[event_sounds]\nStop = \"Hero\"\nNotification = \"Ping\"\n").unwrap();
+        let (settings, sources) =
+            resolve_config(Some(&file), &Config::default(), &Config::default());
+
+        assert_eq!(settings.event_sounds["Stop"], "Hero");
+        assert_eq!(sources["event_sounds"], Source::ConfigFile);
+        assert!(describe(&settings, &sources).contains("[event_sounds]  # config file"));
+    }
+
+    #[test]
+    fn test_quiet_hours_from_config_file() {
+        let file = parse_config_str(
+            "quiet_start = \"22:00\"\nquiet_end = \"07:30\"\nquiet_mode = \"suppress\"\n",
+        )
+        .unwrap();
+        let (settings, sources) =
+            resolve_config(Some(&file), &Config::default(), &Config::default());
+
+        let quiet = settings.quiet_hours.unwrap();
+        assert_eq!(quiet.start, quiet::parse_time("22:00").unwrap());
+        assert_eq!(quiet.end, quiet::parse_time("07:30").unwrap());
+        assert_eq!(quiet.mode, QuietMode::Suppress);
+        assert!(describe(&settings, &sources).contains("quiet_end = \"07:30\"  # config file"));
+    }
+
+    #[test]
+    fn test_quiet_hours_need_both_ends() {
+        let file = parse_config_str("quiet_start = \"22:00\"\n").unwrap();
+        let (settings, _) = resolve_config(Some(&file), &Config::default(), &Config::default());
+
+        assert_eq!(settings.quiet_hours, None);
+        assert!(parse_config_str("quiet_start = \"10pm\"\n")
+            .unwrap_err()
+            .to_string()
+            .contains("expected HH:MM"));
+    }
+
+    #[test]
+    fn test_load_explicit_config() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("work.toml");
+        fs::write(&path, "sound = \"Hero\"\ntitle = \"Work\"\n").unwrap();
+
+        let file = load_explicit_config(&path).unwrap();
+        let cli = Config {
+            sound: Some("Ping".to_string()),
+            ..Default::default()
+        };
+        let (settings, sources) = resolve_config(Some(&file), &Config::default(), &cli);
+
+        assert_eq!(settings.title, "Work");
+        assert_eq!(sources["title"], Source::ConfigFile);
+        assert_eq!(settings.sound, "Ping");
+        assert_eq!(sources["sound"], Source::Cli);
+    }
+
+    #[test]
+    fn test_load_explicit_config_missing_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("missing.toml");
+
+        let err = load_explicit_config(&path).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+
+        fs::write(&path, "sound = \n").unwrap();
+        assert!(load_explicit_config(&path).is_err());
+    }
+
+    const PROFILES: &str = r#"
+sound = "Glass"
+title = "Claude"
+
+timeout_ms = 5000
+
+[profiles.work]
+sound = "Submarine"
+title = "Claude (work)"
+timeout_ms = 20000
+
+[profiles.personal]
+sound_on = "error"
+"#;
+
+    #[test]
+    fn test_apply_custom_sound() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let config = parse_config_str(
+            r#"
+[custom_sound]
+data = "UklGRg=="
+extension = "wav"
+"#,
+        )
+        .unwrap();
+
+        let applied = apply_custom_sound(config.clone(), temp_dir.path()).unwrap();
+        let sound = applied.sound.unwrap();
+        assert!(sound.ends_with(".wav"));
+        assert_eq!(fs::read(&sound).unwrap(), b"RIFF");
+        assert_eq!(applied.custom_sound, None);
+
+        let explicit = Config {
+            sound: Some("Ping".to_string()),
+            ..config
+        };
+        assert_eq!(
+            apply_custom_sound(explicit, temp_dir.path()).unwrap().sound,
+            Some("Ping".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_profile_named() {
+        let file = parse_config_str(PROFILES).unwrap();
+
+        let work = resolve_profile(&file, Some("work")).unwrap();
+        assert_eq!(work.sound, Some("Submarine".to_string()));
+        assert_eq!(work.title, Some("Claude (work)".to_string()));
+        assert_eq!(work.timeout_ms, Some(20000));
+
+        let personal = resolve_profile(&file, Some("personal")).unwrap();
+        assert_eq!(personal.sound, Some("Glass".to_string()));
+        assert_eq!(personal.sound_on, Some(SoundPolicy::Error));
+        assert_eq!(personal.timeout_ms, Some(5000));
+    }
+
+    #[test]
+    fn test_profile_timeout_below_cli() {
+        let file = parse_config_str(PROFILES).unwrap();
+        let work = resolve_profile(&file, Some("work")).unwrap();
+
+        let (settings, sources) =
+            resolve_config(Some(&work), &Config::default(), &Config::default());
+        assert_eq!(settings.timeout_ms, Some(20000));
+        assert_eq!(sources["timeout_ms"], Source::ConfigFile);
+
+        let cli = Config {
+            timeout_ms: Some(1000),
+            ..Default::default()
+        };
+        let (settings, _) = resolve_config(Some(&work), &Config::default(), &cli);
+        assert_eq!(settings.timeout_ms, Some(1000));
+
+        let (settings, _) = resolve_config(None, &Config::default(), &Config::default());
+        assert_eq!(settings.timeout_ms, None);
+    }
+
+    #[test]
+    fn test_resolve_profile_default() {
+        let file = parse_config_str(PROFILES).unwrap();
+
+        assert_eq!(resolve_profile(&file, None).unwrap(), file);
+    }
+
+    #[test]
+    fn test_resolve_profile_unknown() {
+        let file = parse_config_str(PROFILES).unwrap();
+
+        let err = resolve_profile(&file, Some("home")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile 'home' (available: personal, work)"
+        );
+    }
+
+    #[test]
+    fn test_load_config() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("config.toml");
+
+        assert_eq!(load_config(&path).unwrap(), None);
+
+        fs::write(
+            &path,
+            "sound = \"Hero\"\nsound_on = \"error\"\n\n[volumes]\nBasso = 0.5\n",
+        )
+        .unwrap();
+        let config = load_config(&path).unwrap().unwrap();
+        assert_eq!(config.sound, Some("Hero".to_string()));
+        assert_eq!(config.sound_on, Some(SoundPolicy::Error));
+        assert_eq!(config.volumes.get("Basso"), Some(&0.5));
+    }
+
+    #[test]
+    fn test_describe_annotates_sources() {
+        let cli = Config {
+            sound: Some("Ping".to_string()),
+            ..Default::default()
+        };
+        let (settings, sources) = resolve_config(None, &Config::default(), &cli);
+        let output = describe(&settings, &sources);
+
+        assert!(output.contains("sound = \"Ping\"  # command line"));
+        assert!(output.contains("sound_on = \"always\"  # default"));
+    }
+
+    #[test]
+    fn test_parse_config_str_with_comments() {
+        let config = parse_config_str(
+            "# Notification defaults\nsound = \"Hero\" # a louder sound\n\n[volumes]\n# Basso is loud\nBasso = 0.5\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.sound, Some("Hero".to_string()));
+        assert_eq!(config.volumes.get("Basso"), Some(&0.5));
+    }
+
+    #[test]
+    fn test_parse_config_str_reports_location() {
+        let err = parse_config_str("sound = \"Hero\"\nsound_on = always\n").unwrap_err();
+        let message = err.to_string();
+
+        assert!(message.starts_with("line 2, column 12:"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_config_str_trailing_comma() {
+        // TOML doesn't allow a trailing comma in an inline table
+        let err = parse_config_str("volumes = { Basso = 0.5, }\n").unwrap_err();
+        let message = err.to_string();
+
+        assert!(message.starts_with("line 1, column 24:"), "{}", message);
+        assert!(message.contains("invalid inline table"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_config_str_unknown_key() {
+        let err = parse_config_str("sond = \"Hero\"\n").unwrap_err();
+        let message = err.to_string();
+
+        assert!(message.starts_with("line 1, column 1:"), "{}", message);
+        assert!(message.contains("sond"), "{}", message);
+    }
+}
@@ -0,0 +1,687 @@
+use anyhow::{Context, Result};
+use claude_code_notification::split_player_args;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::style;
+
+const BINARY_NAME: &str = "claude-code-notification";
+
+/// The Claude Code settings schema published on SchemaStore.
+#[cfg(feature = "schema")]
+const SETTINGS_SCHEMA_URL: &str = "https://www.schemastore.org/claude-code-settings.json";
+
+/// The outcome of a single doctor check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckResult {
+    Pass(String),
+    Warn(String),
+    Fail(String),
+}
+
+impl CheckResult {
+    fn render(&self) -> String {
+        match self {
+            CheckResult::Pass(msg) => style::ok(&format!("✅ {}", msg)),
+            CheckResult::Warn(msg) => style::warn(&format!("⚠️  {}", msg)),
+            CheckResult::Fail(msg) => style::err(&format!("❌ {}", msg)),
+        }
+    }
+}
+
+/// Collects every hook command in `settings`, across all events.
+fn hook_commands(settings: &Value) -> Vec<&str> {
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    events
+        .values()
+        .filter_map(Value::as_array)
+        .flatten()
+        .filter_map(|group| group.get("hooks").and_then(Value::as_array))
+        .flatten()
+        .filter_map(|entry| entry.get("command").and_then(Value::as_str))
+        .collect()
+}
+
+/// One hook command from the settings file, with the event and matcher it
+/// was registered under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookEntry {
+    pub event: String,
+    pub matcher: Option<String>,
+    pub command: String,
+}
+
+/// Flattens every hook in `settings` into a list, in the order they appear.
+/// Entries without a command are skipped.
+pub fn list_hooks(settings: &Value) -> Vec<HookEntry> {
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for (event, groups) in events {
+        for group in groups.as_array().into_iter().flatten() {
+            let matcher = group
+                .get("matcher")
+                .and_then(Value::as_str)
+                .filter(|m| !m.is_empty())
+                .map(str::to_string);
+            let hooks = group.get("hooks").and_then(Value::as_array);
+            for hook in hooks.into_iter().flatten() {
+                if let Some(command) = hook.get("command").and_then(Value::as_str) {
+                    entries.push(HookEntry {
+                        event: event.clone(),
+                        matcher: matcher.clone(),
+                        command: command.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// True when `command` runs this tool rather than some other program.
+fn is_notification_command(command: &str) -> bool {
+    split_player_args(command)
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .is_some_and(|name| name == BINARY_NAME)
+}
+
+/// The hook's program and arguments with `--dry-run` added, so it prints
+/// what it would show without a sound, delivery, or state changes. `None`
+/// when the command uses shell syntax, since the flag couldn't be placed
+/// safely.
+fn dry_run_command(entry: &HookEntry) -> Option<Vec<String>> {
+    if has_shell_syntax(&entry.command) {
+        return None;
+    }
+    let mut args = split_player_args(&entry.command);
+    args.push("--dry-run".to_string());
+    Some(args)
+}
+
+/// True when `command` has unquoted operators, redirects, or substitutions,
+/// i.e. is more than a single simple invocation.
+fn has_shell_syntax(command: &str) -> bool {
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                chars.by_ref().take_while(|c| *c != '\'').for_each(drop);
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '$' | '`' => return true,
+                        '\\' => {
+                            chars.next();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            '\\' => {
+                chars.next();
+            }
+            '|' | '&' | ';' | '<' | '>' | '(' | ')' | '$' | '`' | '\n' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Runs a notification hook's `args`, from [`dry_run_command`], directly
+/// with a sample payload and returns what it printed.
+fn dry_run_hook(entry: &HookEntry, args: &[String]) -> Result<String> {
+    let payload = json!({
+        "session_id": "hooks-list-test",
+        "transcript_path": "",
+        "message": format!("Test of the {} hook", entry.event),
+        "hook_event_name": entry.event,
+    });
+    let mut child = std::process::Command::new(&args[0])
+        .args(&args[1..])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", args[0]))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        std::io::Write::write_all(&mut stdin, payload.to_string().as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "exited with {:?}: {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn run_hooks_list(settings_path: &Path, test: bool) -> Result<()> {
+    let content = fs::read_to_string(settings_path)
+        .with_context(|| format!("Failed to read settings file {}", settings_path.display()))?;
+    let settings: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", settings_path.display()))?;
+
+    let entries = list_hooks(&settings);
+    if entries.is_empty() {
+        println!("No hooks in {}", settings_path.display());
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for entry in &entries {
+        match &entry.matcher {
+            Some(matcher) => println!("{} [{}]: {}", entry.event, matcher, entry.command),
+            None => println!("{}: {}", entry.event, entry.command),
+        }
+        if !test || !is_notification_command(&entry.command) {
+            continue;
+        }
+        let result = match dry_run_command(entry).map(|args| dry_run_hook(entry, &args)) {
+            Some(Ok(output)) => CheckResult::Pass(format!("Printed \"{}\"", output)),
+            Some(Err(e)) => {
+                failed += 1;
+                CheckResult::Fail(format!("Test run failed: {}", e))
+            }
+            None => {
+                CheckResult::Warn("Skipped the test run: the command uses shell syntax".to_string())
+            }
+        };
+        println!("  {}", result.render());
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} hook(s) failed their test run", failed);
+    }
+    Ok(())
+}
+
+/// Resolves a command's program the way the shell would: paths are taken as
+/// given (with `~/` expanded), bare names are looked up on `PATH`.
+fn resolve_program(program: &str) -> Option<PathBuf> {
+    if let Some(rest) = program.strip_prefix("~/") {
+        let home = std::env::var_os("HOME")?;
+        return Some(PathBuf::from(home).join(rest)).filter(|p| p.is_file());
+    }
+    if program.contains('/') {
+        return Some(PathBuf::from(program)).filter(|p| p.is_file());
+    }
+
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Checks that the notifier hook in `settings` runs `current_exe`, catching
+/// hooks left pointing at an old or removed install.
+pub fn check_hook_binary(settings: &Value, current_exe: &Path) -> CheckResult {
+    let programs: Vec<String> = hook_commands(settings)
+        .into_iter()
+        .filter_map(|command| split_player_args(command).into_iter().next())
+        .filter(|program| {
+            Path::new(program)
+                .file_name()
+                .is_some_and(|name| name == BINARY_NAME)
+        })
+        .collect();
+
+    let Some(first) = programs.first() else {
+        return CheckResult::Fail(format!("No {} hook found in settings", BINARY_NAME));
+    };
+
+    for program in &programs {
+        match resolve_program(program) {
+            None => {
+                return CheckResult::Fail(format!("Hook runs '{}', which doesn't exist", program))
+            }
+            Some(path) if !same_file(&path, current_exe) => {
+                return CheckResult::Warn(format!(
+                    "Hook runs {} but this is {}; the hook may point at a stale install",
+                    path.display(),
+                    current_exe.display()
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    CheckResult::Pass(format!("Hook runs this binary ({})", first))
+}
+
+/// The outcome of each `validate` check, in the order they ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl ValidationReport {
+    /// True when no check failed; warnings don't count.
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check, CheckResult::Fail(_)))
+    }
+}
+
+/// Fetches the Claude Code settings schema, or `None` when it can't be
+/// downloaded or parsed.
+#[cfg(feature = "schema")]
+fn fetch_settings_schema() -> Option<Value> {
+    reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .ok()?
+        .get(SETTINGS_SCHEMA_URL)
+        .send()
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .ok()
+}
+
+/// Checks `settings` against the Claude Code settings `schema`, warning
+/// when there is no schema to check against.
+#[cfg(feature = "schema")]
+fn check_schema(settings: &Value, schema: Option<&Value>) -> CheckResult {
+    match schema.map(jsonschema::validator_for) {
+        None => {
+            CheckResult::Warn("Skipped the schema check: couldn't fetch the schema".to_string())
+        }
+        Some(Err(e)) => CheckResult::Warn(format!("Skipped the schema check: {}", e)),
+        Some(Ok(validator)) => {
+            let errors: Vec<String> = validator
+                .iter_errors(settings)
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect();
+            if errors.is_empty() {
+                CheckResult::Pass("Settings match the Claude Code schema".to_string())
+            } else {
+                CheckResult::Fail(format!(
+                    "Settings don't match the Claude Code schema:\n  {}",
+                    errors.join("\n  ")
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "schema"))]
+fn check_schema(_settings: &Value, _schema: Option<&Value>) -> CheckResult {
+    CheckResult::Warn("Skipped the schema check: built without the `schema` feature".to_string())
+}
+
+/// Validates the settings file at `path` without running Claude: that it
+/// parses, matches the published settings schema, and has a `Notification`
+/// hook whose programs exist.
+pub fn validate_settings(path: &Path) -> Result<ValidationReport> {
+    #[cfg(feature = "schema")]
+    let schema = fetch_settings_schema();
+    #[cfg(not(feature = "schema"))]
+    let schema = None;
+    validate_settings_with(path, schema.as_ref())
+}
+
+/// Like [`validate_settings`], checking against `schema` when there is one.
+fn validate_settings_with(path: &Path, schema: Option<&Value>) -> Result<ValidationReport> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read settings file {}", path.display()))?;
+    let mut checks = Vec::new();
+
+    let settings: Value = match serde_json::from_str(&content) {
+        Ok(settings) => settings,
+        Err(e) => {
+            checks.push(CheckResult::Fail(format!(
+                "Settings aren't valid JSON: {}",
+                e
+            )));
+            return Ok(ValidationReport { checks });
+        }
+    };
+    checks.push(CheckResult::Pass("Settings are valid JSON".to_string()));
+
+    checks.push(check_schema(&settings, schema));
+
+    let notification =
+        json!({ "hooks": { "Notification": settings.pointer("/hooks/Notification") } });
+    let commands = hook_commands(&notification);
+    if commands.is_empty() {
+        checks.push(CheckResult::Fail("No Notification hook found".to_string()));
+    }
+    for command in commands {
+        let program = split_player_args(command)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        checks.push(match resolve_program(&program) {
+            Some(path) => CheckResult::Pass(format!("Notification hook runs {}", path.display())),
+            None => CheckResult::Fail(format!(
+                "Notification hook runs '{}', which doesn't exist",
+                program
+            )),
+        });
+    }
+
+    Ok(ValidationReport { checks })
+}
+
+pub fn run_validate(settings_path: &Path) -> Result<()> {
+    println!("🔎 Validating {}\n", settings_path.display());
+
+    let report = validate_settings(settings_path)?;
+    for check in &report.checks {
+        println!("{}", check.render());
+    }
+
+    if !report.passed() {
+        anyhow::bail!("Settings are not valid");
+    }
+    println!("\nSettings are valid.");
+    Ok(())
+}
+
+pub fn run_doctor(settings_path: &Path) -> Result<()> {
+    println!("🩺 Checking Claude Code notifications\n");
+    println!("📁 Settings file: {}", settings_path.display());
+
+    let settings: Value = match fs::read_to_string(settings_path) {
+        Ok(content) => serde_json::from_str(&content)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Value::Null,
+        Err(e) => return Err(e.into()),
+    };
+
+    let results = [check_hook_binary(&settings, &std::env::current_exe()?)];
+    for result in &results {
+        println!("{}", result.render());
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| matches!(r, CheckResult::Fail(_)))
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn settings_with(command: &str) -> Value {
+        json!({
+            "hooks": {
+                "Notification": [
+                    {"hooks": [{"type": "command", "command": command}]}
+                ]
+            }
+        })
+    }
+
+    fn install(dir: &Path, subdir: &str) -> PathBuf {
+        let path = dir.join(subdir).join(BINARY_NAME);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_check_hook_binary_matching() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let exe = install(temp_dir.path(), "bin");
+        let settings = settings_with(&format!("{} --sound Glass", exe.display()));
+
+        assert!(matches!(
+            check_hook_binary(&settings, &exe),
+            CheckResult::Pass(_)
+        ));
+    }
+
+    #[test]
+    fn test_check_hook_binary_mismatching() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let old = install(temp_dir.path(), "old");
+        let exe = install(temp_dir.path(), "new");
+        let settings = settings_with(&format!("{} --sound Glass", old.display()));
+
+        assert!(matches!(
+            check_hook_binary(&settings, &exe),
+            CheckResult::Warn(_)
+        ));
+    }
+
+    #[test]
+    fn test_check_hook_binary_removed_install() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let exe = install(temp_dir.path(), "bin");
+        let removed = temp_dir.path().join("gone").join(BINARY_NAME);
+        let settings = settings_with(&format!("\"{}\"", removed.display()));
+
+        assert!(matches!(
+            check_hook_binary(&settings, &exe),
+            CheckResult::Fail(_)
+        ));
+    }
+
+    fn hooks_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "hooks": {
+                    "type": "object",
+                    "additionalProperties": {"type": "array"}
+                }
+            }
+        })
+    }
+
+    fn write_settings(dir: &Path, settings: &str) -> PathBuf {
+        let path = dir.join("settings.json");
+        fs::write(&path, settings).unwrap();
+        path
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_settings_valid() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let exe = install(temp_dir.path(), "bin");
+        let path = write_settings(
+            temp_dir.path(),
+            &settings_with(&format!("{} --sound Glass", exe.display())).to_string(),
+        );
+
+        let report = validate_settings_with(&path, Some(&hooks_schema())).unwrap();
+
+        assert!(report.passed());
+        assert_eq!(report.checks.len(), 3);
+        assert!(report
+            .checks
+            .iter()
+            .all(|check| matches!(check, CheckResult::Pass(_))));
+    }
+
+    #[test]
+    fn test_validate_settings_invalid_json() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_settings(temp_dir.path(), "{\"hooks\": ");
+
+        let report = validate_settings_with(&path, Some(&hooks_schema())).unwrap();
+
+        assert!(!report.passed());
+        assert_eq!(report.checks.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_settings_schema_and_hook_failures() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_settings(
+            temp_dir.path(),
+            r#"{"hooks": {"Stop": "say done", "Notification": [{"hooks": [{"type": "command", "command": "/nonexistent/claude-code-notification"}]}]}}"#,
+        );
+
+        let report = validate_settings_with(&path, Some(&hooks_schema())).unwrap();
+
+        assert!(!report.passed());
+        assert!(matches!(&report.checks[1], CheckResult::Fail(msg) if msg.contains("/hooks/Stop")));
+        assert!(
+            matches!(&report.checks[2], CheckResult::Fail(msg) if msg.contains("doesn't exist"))
+        );
+    }
+
+    #[test]
+    fn test_validate_settings_without_hook_or_schema() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_settings(temp_dir.path(), r#"{"model": "opus"}"#);
+
+        let report = validate_settings_with(&path, None).unwrap();
+
+        assert!(matches!(report.checks[1], CheckResult::Warn(_)));
+        assert_eq!(
+            report.checks[2],
+            CheckResult::Fail("No Notification hook found".to_string())
+        );
+        assert!(validate_settings_with(&temp_dir.path().join("missing.json"), None).is_err());
+    }
+
+    #[test]
+    fn test_list_hooks_multiple_events() {
+        let settings = json!({
+            "hooks": {
+                "Notification": [
+                    {"hooks": [{"type": "command", "command": "claude-code-notification --sound Glass"}]}
+                ],
+                "PreToolUse": [
+                    {
+                        "matcher": "Bash",
+                        "hooks": [
+                            {"type": "command", "command": "claude-code-notification --title Bash"},
+                            {"type": "command", "command": "./audit.sh"}
+                        ]
+                    },
+                    {"matcher": "", "hooks": [{"type": "command", "command": "./log.sh"}]}
+                ],
+                "Stop": [{"hooks": [{"type": "prompt"}]}]
+            }
+        });
+
+        let entry = |event: &str, matcher: Option<&str>, command: &str| HookEntry {
+            event: event.to_string(),
+            matcher: matcher.map(str::to_string),
+            command: command.to_string(),
+        };
+        assert_eq!(
+            list_hooks(&settings),
+            vec![
+                entry(
+                    "Notification",
+                    None,
+                    "claude-code-notification --sound Glass"
+                ),
+                entry(
+                    "PreToolUse",
+                    Some("Bash"),
+                    "claude-code-notification --title Bash"
+                ),
+                entry("PreToolUse", Some("Bash"), "./audit.sh"),
+                entry("PreToolUse", None, "./log.sh"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_hooks_malformed() {
+        assert!(list_hooks(&json!({})).is_empty());
+        assert!(list_hooks(&json!({"hooks": {"Stop": "say done"}})).is_empty());
+        assert!(list_hooks(&json!({"hooks": {"Stop": [{"matcher": "x"}]}})).is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_command() {
+        let entry = HookEntry {
+            event: "Notification".to_string(),
+            matcher: None,
+            command: "claude-code-notification --webhook https://example.com --copy".to_string(),
+        };
+
+        assert_eq!(
+            dry_run_command(&entry).unwrap(),
+            [
+                "claude-code-notification",
+                "--webhook",
+                "https://example.com",
+                "--copy",
+                "--dry-run"
+            ]
+        );
+
+        let entry = HookEntry {
+            command: r#"claude-code-notification --message-template "a | b; 'c'""#.to_string(),
+            ..entry
+        };
+        assert_eq!(dry_run_command(&entry).unwrap()[2], "a | b; 'c'");
+    }
+
+    #[test]
+    fn test_dry_run_command_rejects_compound_commands() {
+        for command in [
+            "claude-code-notification --sound Glass || true",
+            "claude-code-notification 2>/dev/null",
+            "claude-code-notification; say done",
+            "claude-code-notification --title \"$USER\"",
+            "claude-code-notification --title $(hostname)",
+        ] {
+            let entry = HookEntry {
+                event: "Notification".to_string(),
+                matcher: None,
+                command: command.to_string(),
+            };
+            assert_eq!(dry_run_command(&entry), None, "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_is_notification_command() {
+        assert!(is_notification_command(
+            "/usr/local/bin/claude-code-notification --sound Glass"
+        ));
+        assert!(!is_notification_command("./audit.sh"));
+        assert!(!is_notification_command(""));
+    }
+
+    #[test]
+    fn test_check_hook_binary_missing_command() {
+        let exe = Path::new("/usr/local/bin/claude-code-notification");
+
+        assert!(matches!(
+            check_hook_binary(&json!({}), exe),
+            CheckResult::Fail(_)
+        ));
+        assert!(matches!(
+            check_hook_binary(&settings_with("afplay /tmp/a.wav"), exe),
+            CheckResult::Fail(_)
+        ));
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::stable_hash;
+
+/// A sound stored in the config file itself (the `[custom_sound]` table).
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EmbeddedSound {
+    /// Base64-encoded audio; whitespace is ignored so it can be wrapped.
+    pub data: String,
+    /// File extension that tells the player the format, e.g. `wav`.
+    pub extension: String,
+}
+
+/// Decodes an embedded sound into `cache`, returning the file's path. Files
+/// are named by a hash of their content, so an unchanged sound is decoded
+/// once and reused on later runs.
+pub fn materialize_embedded_sound(b64: &str, ext: &str, cache: &Path) -> Result<PathBuf> {
+    let ext = ext.trim_start_matches('.');
+    if ext.is_empty() || ext.len() > 4 || !ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+        anyhow::bail!("Invalid sound extension '{}'", ext);
+    }
+
+    let data: String = b64.chars().filter(|c| !c.is_whitespace()).collect();
+    let path = cache.join(format!("{:016x}.{}", stable_hash(data.as_bytes()), ext));
+    if path.exists() {
+        return Ok(path);
+    }
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&data)
+        .context("Sound data is not valid base64")?;
+
+    // Write under a temporary name first so an interrupted write never
+    // leaves a truncated file that would be treated as a cache hit.
+    fs::create_dir_all(cache)?;
+    let partial = path.with_extension("partial");
+    fs::write(&partial, bytes)?;
+    fs::rename(&partial, &path)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_materialize_decodes_and_writes() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let cache = temp_dir.path().join("embedded");
+
+        let path = materialize_embedded_sound("UklG\nRg==", ".wav", &cache).unwrap();
+
+        assert_eq!(path.parent(), Some(cache.as_path()));
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("wav"));
+        assert_eq!(fs::read(&path).unwrap(), b"RIFF");
+    }
+
+    #[test]
+    fn test_materialize_reuses_cached_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let first = materialize_embedded_sound("UklGRg==", "wav", temp_dir.path()).unwrap();
+        fs::write(&first, b"cached").unwrap();
+
+        let second = materialize_embedded_sound("UklGRg==", "wav", temp_dir.path()).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(fs::read(&second).unwrap(), b"cached");
+        assert_ne!(
+            materialize_embedded_sound("AAAA", "wav", temp_dir.path()).unwrap(),
+            first
+        );
+    }
+
+    #[test]
+    fn test_materialize_rejects_bad_input() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+
+        assert!(materialize_embedded_sound("not base64!", "wav", temp_dir.path()).is_err());
+        assert!(materialize_embedded_sound("UklGRg==", "../x", temp_dir.path()).is_err());
+        assert!(materialize_embedded_sound("UklGRg==", "", temp_dir.path()).is_err());
+    }
+}
@@ -15,10 +15,34 @@ pub enum NotificationError {
 
     #[error("Invalid notification input: {0}")]
     InvalidInput(String),
+
+    #[error("Failed to play sound '{sound}': {reason}")]
+    SoundPlayback { sound: String, reason: String },
 }
 
 impl NotificationError {
     pub fn invalid_input<S: Into<String>>(msg: S) -> Self {
         NotificationError::InvalidInput(msg.into())
     }
+
+    pub fn sound_playback<S: Into<String>, R: Into<String>>(sound: S, reason: R) -> Self {
+        NotificationError::SoundPlayback {
+            sound: sound.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sound_playback_display() {
+        let err = NotificationError::sound_playback("Glass", "afplay exit code: Some(1)");
+        let message = err.to_string();
+
+        assert!(message.contains("Glass"));
+        assert!(message.contains("afplay exit code: Some(1)"));
+    }
 }
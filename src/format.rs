@@ -0,0 +1,761 @@
+use std::time::{Duration, SystemTime};
+
+use crate::NotificationInput;
+
+/// How many characters of the session id `{session_short}` shows.
+const SESSION_SHORT_LEN: usize = 8;
+
+/// Removes ANSI escape sequences and control characters that would render
+/// as garbage in a notification. Newlines are kept, tabs become spaces, and
+/// CSI sequences (e.g. `\x1b[31m`) are dropped along with their parameters,
+/// as are OSC strings (e.g. a terminal title or hyperlink, ended by BEL or
+/// ST) and other `ESC x` escapes.
+pub fn sanitize_message(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next_if(|c| ('\x20'..='\x7e').contains(c)) {
+                Some('[') => {
+                    // Parameter and intermediate bytes run up to a final byte
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    // OSC and other string sequences run up to BEL or ST
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                Some(c) if ('\x20'..='\x2f').contains(&c) => {
+                    // Intermediate bytes, e.g. `ESC ( B`, then a final byte
+                    while chars.next_if(|c| ('\x20'..='\x2f').contains(c)).is_some() {}
+                    chars.next();
+                }
+                // Two-byte escapes such as `ESC =` or `ESC 7`
+                _ => {}
+            },
+            '\n' => out.push('\n'),
+            '\t' => out.push(' '),
+            c if c.is_control() && (c <= '\x1f' || c == '\x7f') => {}
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Removes common markdown syntax so the message reads cleanly as plain text.
+///
+/// Handles bold (`**x**`, `__x__`), inline code, fenced code blocks, and
+/// links/images (`[text](url)` becomes `text`). Delimiters that don't wrap
+/// text in a matching pair, like those in `__init__.py` or `2**10`, are
+/// kept.
+pub fn strip_markdown(s: &str) -> String {
+    let without_fences: Vec<&str> = s
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+    let text = without_fences.join("\n");
+
+    let text = strip_pairs(&text, "**");
+    let text = strip_pairs(&text, "__");
+    let text = strip_pairs(&text, "`");
+    strip_links(&text)
+}
+
+/// Removes `delim` where it wraps non-space text on one line at word
+/// boundaries, keeping the text. Unpaired and intraword delimiters stay.
+fn strip_pairs(s: &str, delim: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    let mut before: Option<char> = None;
+
+    while let Some(open) = find_opening(rest, delim, before) {
+        let inner_start = open + delim.len();
+        match find_closing(&rest[inner_start..], delim) {
+            Some(close) => {
+                result.push_str(&rest[..open]);
+                result.push_str(&rest[inner_start..inner_start + close]);
+                rest = &rest[inner_start + close + delim.len()..];
+            }
+            None => {
+                result.push_str(&rest[..inner_start]);
+                rest = &rest[inner_start..];
+            }
+        }
+        before = result.chars().next_back();
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Finds a `delim` in `s` that can open a pair: at the start of a word and
+/// followed by non-space text. `before` is the character preceding `s`.
+fn find_opening(s: &str, delim: &str, before: Option<char>) -> Option<usize> {
+    s.match_indices(delim).map(|(i, _)| i).find(|&i| {
+        let prev = if i == 0 {
+            before
+        } else {
+            s[..i].chars().next_back()
+        };
+        let starts_word = prev.is_none_or(|c| c.is_whitespace() || "([{\"'".contains(c));
+        let next = s[i + delim.len()..].chars().next();
+        starts_word && next.is_some_and(|c| !c.is_whitespace() && !delim.starts_with(c))
+    })
+}
+
+/// Finds a `delim` in `s`, before the end of the line, that closes a pair:
+/// after non-space text and at the end of a word.
+fn find_closing(s: &str, delim: &str) -> Option<usize> {
+    let line = s.split('\n').next().unwrap_or_default();
+    line.match_indices(delim).map(|(i, _)| i).find(|&i| {
+        let prev = line[..i].chars().next_back();
+        let mut after = line[i + delim.len()..].chars();
+        let ends_word = match after.next() {
+            None => true,
+            Some(c) if c.is_whitespace() => true,
+            Some(c) if ".,;:!?)]}\"'".contains(c) => {
+                after.next().is_none_or(|c| !c.is_alphanumeric())
+            }
+            Some(_) => false,
+        };
+        prev.is_some_and(|c| !c.is_whitespace()) && ends_word
+    })
+}
+
+/// Replaces `[text](url)` and `![alt](url)` with just the text.
+fn strip_links(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(open) = rest.find('[') {
+        let Some(link) = parse_link(&rest[open..]) else {
+            result.push_str(&rest[..=open]);
+            rest = &rest[open + 1..];
+            continue;
+        };
+
+        let prefix = &rest[..open];
+        result.push_str(prefix.strip_suffix('!').unwrap_or(prefix));
+        result.push_str(link.0);
+        rest = &rest[open + link.1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Parses a `[text](url)` link at the start of `s`, returning the text and
+/// the total length of the link syntax.
+fn parse_link(s: &str) -> Option<(&str, usize)> {
+    let close = s.find(']')?;
+    let after = &s[close + 1..];
+    if !after.starts_with('(') {
+        return None;
+    }
+    let end = after.find(')')?;
+
+    Some((&s[1..close], close + 1 + end + 1))
+}
+
+/// Hard-wraps each line of `s` at word boundaries so no line exceeds
+/// `width` characters. Words longer than `width` are split. A width of 0
+/// disables wrapping.
+pub fn wrap_body(s: &str, width: usize) -> String {
+    if width == 0 {
+        return s.to_string();
+    }
+
+    s.lines()
+        .map(|line| wrap_line(line, width))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn wrap_line(line: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for word in line.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+
+        // Split words that can never fit on a line of their own
+        while word.len() > width {
+            if current_len > 0 {
+                lines.push(std::mem::take(&mut current));
+                current_len = 0;
+            }
+            lines.push(word.drain(..width).collect());
+        }
+
+        if word.is_empty() {
+            continue;
+        }
+
+        if current_len > 0 && current_len + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+
+        if current_len > 0 {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.extend(word.iter());
+        current_len += word.len();
+    }
+
+    if current_len > 0 || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines.join("\n")
+}
+
+/// Keeps `s` up to and including its `n`th non-empty line, appending "…"
+/// when later non-empty lines were dropped. Blank lines and indentation
+/// between the kept lines are preserved. An `n` of 0 disables truncation.
+pub fn first_n_lines(s: &str, n: usize) -> String {
+    if n == 0 {
+        return s.to_string();
+    }
+
+    let mut kept = Vec::new();
+    let mut lines = s.lines();
+    let mut count = 0;
+    for line in lines.by_ref() {
+        kept.push(line);
+        if !line.trim().is_empty() {
+            count += 1;
+            if count == n {
+                break;
+            }
+        }
+    }
+
+    if count < n {
+        return s.to_string();
+    }
+
+    let mut summary = kept.join("\n");
+    if lines.any(|line| !line.trim().is_empty()) {
+        summary.push('…');
+    }
+    summary
+}
+
+/// Shortens `s` to at most `max` characters, ending with "…" when anything
+/// was cut. Counts chars rather than bytes so multi-byte text is never split.
+/// A `max` of 0 disables truncation.
+pub fn truncate_chars(s: &str, max: usize) -> String {
+    if max == 0 || s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut truncated: String = s.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Like [`truncate_chars`], but cuts at the last word boundary that fits so
+/// words aren't split. A single word longer than `max` is cut mid-word.
+pub fn truncate_at_word(s: &str, max: usize) -> String {
+    if max == 0 || s.chars().count() <= max {
+        return s.to_string();
+    }
+
+    let kept: String = s.chars().take(max - 1).collect();
+    let next_is_space = s.chars().nth(max - 1).is_some_and(char::is_whitespace);
+    let cut = if next_is_space {
+        Some(kept.as_str())
+    } else {
+        kept.rfind(char::is_whitespace).map(|i| &kept[..i])
+    };
+
+    match cut.map(str::trim_end).filter(|cut| !cut.is_empty()) {
+        Some(cut) => format!("{}…", cut),
+        None => truncate_chars(s, max),
+    }
+}
+
+/// Where [`truncate`] is allowed to cut.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncateMode {
+    /// Cut at any character.
+    #[default]
+    Char,
+    /// Cut at a word boundary.
+    Word,
+}
+
+impl TruncateMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "char" => Some(TruncateMode::Char),
+            "word" => Some(TruncateMode::Word),
+            _ => None,
+        }
+    }
+}
+
+/// Shortens `s` to at most `max` characters using `mode`.
+pub fn truncate(s: &str, max: usize, mode: TruncateMode) -> String {
+    match mode {
+        TruncateMode::Char => truncate_chars(s, max),
+        TruncateMode::Word => truncate_at_word(s, max),
+    }
+}
+
+/// Appends the version of this build on its own line, to tell which
+/// installed copy produced a notification.
+pub fn append_version(body: &str) -> String {
+    format!(
+        "{}\nclaude-code-notification v{}",
+        body,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Renders a `--message-template`. `{message}`, `{session}`,
+/// `{session_short}` (the first 8 characters of the session id), and
+/// `{title}` are replaced; `{{` and `}}` produce literal braces, and unknown
+/// placeholders are left as they are.
+pub fn render_body(template: &str, input: &NotificationInput, title: &str) -> String {
+    let mut out = String::with_capacity(template.len() + input.message.len());
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if let Some(after) = rest.strip_prefix("{{").or_else(|| rest.strip_prefix("}}")) {
+            out.push_str(&rest[..1]);
+            rest = after;
+            continue;
+        }
+
+        let placeholder = rest.strip_prefix('{').and_then(|inner| {
+            let (name, after) = inner.split_once('}')?;
+            let value = match name {
+                "message" => input.message.clone(),
+                "session" => input.session_id.clone(),
+                "session_short" => input.session_id.chars().take(SESSION_SHORT_LEN).collect(),
+                "title" => title.to_string(),
+                _ => return None,
+            };
+            Some((value, after))
+        });
+        match placeholder {
+            Some((value, after)) => {
+                out.push_str(&value);
+                rest = after;
+            }
+            None => {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Formats the time between `start` and `now` compactly, e.g. `45s`,
+/// `2m13s`, or `1h4m9s`. A `start` in the future is treated as no time.
+pub fn format_elapsed(start: SystemTime, now: SystemTime) -> String {
+    format_duration(now.duration_since(start).unwrap_or_default())
+}
+
+/// Formats a duration like [`format_elapsed`], ignoring fractional seconds.
+pub fn format_duration(duration: Duration) -> String {
+    let total = duration.as_secs();
+    let (hours, minutes, seconds) = (total / 3600, (total % 3600) / 60, total % 60);
+
+    if hours > 0 {
+        format!("{}h{}m{}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m{}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// How the transcript path is shown in the notification body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathDisplay {
+    /// The path exactly as provided.
+    Full,
+    /// The path with the home directory collapsed and middle components elided.
+    Short,
+    /// Only the file name.
+    Basename,
+}
+
+impl PathDisplay {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(PathDisplay::Full),
+            "short" => Some(PathDisplay::Short),
+            "basename" => Some(PathDisplay::Basename),
+            _ => None,
+        }
+    }
+}
+
+/// Collapses the home directory to `~` and replaces all but the first and
+/// last two components of long paths with `…`.
+pub fn abbreviate_path(path: &str, home: &str) -> String {
+    let home = home.trim_end_matches('/');
+    let path = match path.strip_prefix(home) {
+        Some(rest) if !home.is_empty() && (rest.is_empty() || rest.starts_with('/')) => {
+            format!("~{}", rest)
+        }
+        _ => path.to_string(),
+    };
+
+    let components: Vec<&str> = path.split('/').collect();
+    if components.len() <= 4 {
+        return path;
+    }
+
+    let tail = &components[components.len() - 2..];
+    format!("{}/…/{}", components[0], tail.join("/"))
+}
+
+/// Formats a path for display according to `mode`.
+pub fn display_path(path: &str, home: &str, mode: PathDisplay) -> String {
+    match mode {
+        PathDisplay::Full => path.to_string(),
+        PathDisplay::Short => abbreviate_path(path, home),
+        PathDisplay::Basename => path.rsplit('/').next().unwrap_or(path).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_input() -> NotificationInput {
+        NotificationInput {
+            session_id: "0123456789abcdef".to_string(),
+            transcript_path: "/tmp/t.jsonl".to_string(),
+            message: "Build finished".to_string(),
+            title: None,
+            hook_event_name: None,
+            stop_hook_active: None,
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_render_body_placeholders() {
+        let input = template_input();
+        let render = |template| render_body(template, &input, "Claude Code");
+
+        assert_eq!(render("🤖 {message}"), "🤖 Build finished");
+        assert_eq!(render("{session}"), "0123456789abcdef");
+        assert_eq!(render("— session {session_short}"), "— session 01234567");
+        assert_eq!(
+            render("[{title}] {message}"),
+            "[Claude Code] Build finished"
+        );
+        assert_eq!(
+            render("{message}\n— session {session_short}"),
+            "Build finished\n— session 01234567"
+        );
+    }
+
+    #[test]
+    fn test_render_body_short_session() {
+        let input = NotificationInput {
+            session_id: "abc".to_string(),
+            ..template_input()
+        };
+
+        assert_eq!(render_body("{session_short}", &input, ""), "abc");
+    }
+
+    #[test]
+    fn test_render_body_escapes_and_unknown_placeholders() {
+        let input = template_input();
+        let render = |template| render_body(template, &input, "Claude Code");
+
+        assert_eq!(
+            render("{{message}} is {message}"),
+            "{message} is Build finished"
+        );
+        assert_eq!(render("a }} b {{"), "a } b {");
+        assert_eq!(render("{unknown} {message}"), "{unknown} Build finished");
+        assert_eq!(render("{ {message"), "{ {message");
+        assert_eq!(render("open { and close }"), "open { and close }");
+    }
+
+    #[test]
+    fn test_strip_markdown_bold() {
+        assert_eq!(strip_markdown("This is **important**"), "This is important");
+        assert_eq!(strip_markdown("__also bold__ text"), "also bold text");
+    }
+
+    #[test]
+    fn test_strip_markdown_inline_code() {
+        assert_eq!(
+            strip_markdown("Run `cargo test` to verify"),
+            "Run cargo test to verify"
+        );
+    }
+
+    #[test]
+    fn test_strip_markdown_fenced_code_block() {
+        let input = "Try this:\n```bash\ncargo build\n```\nDone";
+        assert_eq!(strip_markdown(input), "Try this:\ncargo build\nDone");
+    }
+
+    #[test]
+    fn test_strip_markdown_links() {
+        assert_eq!(
+            strip_markdown("See [the docs](https://example.com) for more"),
+            "See the docs for more"
+        );
+        assert_eq!(strip_markdown("![logo](logo.png)"), "logo");
+        assert_eq!(strip_markdown("array[0] (first)"), "array[0] (first)");
+    }
+
+    #[test]
+    fn test_strip_markdown_plain_text_unchanged() {
+        let input = "Claude needs your permission to use Bash";
+        assert_eq!(strip_markdown(input), input);
+    }
+
+    #[test]
+    fn test_strip_markdown_keeps_unpaired_delimiters() {
+        for input in [
+            "Edited __init__.py",
+            "Renamed snake__case and a__b",
+            "2**10 is 1024",
+            "Use a ` or ** alone",
+            "it's `half open",
+        ] {
+            assert_eq!(strip_markdown(input), input);
+        }
+    }
+
+    #[test]
+    fn test_strip_markdown_pairs_next_to_punctuation() {
+        assert_eq!(
+            strip_markdown("Done: **all tests passed**. Run `make`, then (__deploy__)"),
+            "Done: all tests passed. Run make, then (deploy)"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_at_word_boundaries() {
+        assert_eq!(
+            wrap_body("the quick brown fox jumps over the lazy dog", 10),
+            "the quick\nbrown fox\njumps over\nthe lazy\ndog"
+        );
+        assert_eq!(wrap_body("short", 10), "short");
+    }
+
+    #[test]
+    fn test_wrap_body_preserves_existing_lines() {
+        assert_eq!(
+            wrap_body("first line here\nsecond", 10),
+            "first line\nhere\nsecond"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_long_unbreakable_token() {
+        assert_eq!(
+            wrap_body("see /a/very/long/path/name ok", 8),
+            "see\n/a/very/\nlong/pat\nh/name\nok"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_width_zero_disabled() {
+        let input = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(wrap_body(input, 0), input);
+    }
+
+    #[test]
+    fn test_format_elapsed_sub_minute() {
+        let start = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(45)),
+            "45s"
+        );
+        assert_eq!(format_elapsed(start, start), "0s");
+    }
+
+    #[test]
+    fn test_format_elapsed_minutes_and_seconds() {
+        let start = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(133)),
+            "2m13s"
+        );
+    }
+
+    #[test]
+    fn test_format_elapsed_hours() {
+        let start = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(3600 + 4 * 60 + 9)),
+            "1h4m9s"
+        );
+        assert_eq!(format_elapsed(start + Duration::from_secs(10), start), "0s");
+    }
+
+    #[test]
+    fn test_abbreviate_path_home_rooted() {
+        let home = "/Users/dev";
+        assert_eq!(
+            abbreviate_path("/Users/dev/.claude/projects/demo/abc.jsonl", home),
+            "~/…/demo/abc.jsonl"
+        );
+        assert_eq!(
+            abbreviate_path("/Users/dev/notes/t.md", home),
+            "~/notes/t.md"
+        );
+        assert_eq!(
+            abbreviate_path("/Users/developer/t.md", home),
+            "/Users/developer/t.md"
+        );
+    }
+
+    #[test]
+    fn test_abbreviate_path_outside_home() {
+        assert_eq!(
+            abbreviate_path("/var/lib/claude/sessions/abc.jsonl", "/Users/dev"),
+            "/…/sessions/abc.jsonl"
+        );
+        assert_eq!(abbreviate_path("/tmp/t.md", "/Users/dev"), "/tmp/t.md");
+    }
+
+    #[test]
+    fn test_display_path_basename() {
+        let path = "/Users/dev/.claude/projects/demo/abc.jsonl";
+        assert_eq!(
+            display_path(path, "/Users/dev", PathDisplay::Basename),
+            "abc.jsonl"
+        );
+        assert_eq!(display_path(path, "/Users/dev", PathDisplay::Full), path);
+    }
+
+    #[test]
+    fn test_first_n_lines_truncates() {
+        assert_eq!(first_n_lines("one\n  two\n\nthree", 2), "one\n  two…");
+        assert_eq!(first_n_lines("one\n\ntwo\nthree", 2), "one\n\ntwo…");
+    }
+
+    #[test]
+    fn test_first_n_lines_fewer_than_n() {
+        assert_eq!(first_n_lines("one\ntwo", 5), "one\ntwo");
+        assert_eq!(first_n_lines("one\ntwo", 2), "one\ntwo");
+    }
+
+    #[test]
+    fn test_first_n_lines_zero_disabled() {
+        assert_eq!(first_n_lines("one\ntwo\nthree", 0), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn test_first_n_lines_trailing_blank_lines() {
+        assert_eq!(first_n_lines("one\ntwo\n\n  \n", 2), "one\ntwo");
+        assert_eq!(first_n_lines("\none\n\n", 1), "\none");
+    }
+
+    #[test]
+    fn test_append_version() {
+        let body = append_version("Done");
+
+        assert!(body.starts_with("Done\n"));
+        assert!(body.ends_with(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("A very long title indeed", 10), "A very lo…");
+        assert_eq!(truncate_chars("Short", 10), "Short");
+        assert_eq!(truncate_chars("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_chars("anything", 0), "anything");
+    }
+
+    #[test]
+    fn test_truncate_chars_multibyte() {
+        assert_eq!(truncate_chars("日本語のタイトル", 4), "日本語…");
+        assert_eq!(truncate_chars("🎉🎉🎉", 2), "🎉…");
+    }
+
+    #[test]
+    fn test_truncate_at_word_sentence() {
+        assert_eq!(truncate_at_word("A very long title indeed", 10), "A very…");
+        assert_eq!(truncate_at_word("Build finished ok", 15), "Build finished…");
+    }
+
+    #[test]
+    fn test_truncate_at_word_long_word() {
+        assert_eq!(truncate_at_word("Supercalifragilistic", 10), "Supercali…");
+        assert_eq!(truncate_at_word(" Supercalifragilistic", 10), " Supercal…");
+    }
+
+    #[test]
+    fn test_truncate_at_word_short_input() {
+        assert_eq!(truncate_at_word("Short", 10), "Short");
+        assert_eq!(truncate_at_word("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_at_word("anything goes", 0), "anything goes");
+    }
+
+    #[test]
+    fn test_truncate_mode() {
+        let title = "A very long title indeed";
+        assert_eq!(truncate(title, 10, TruncateMode::Char), "A very lo…");
+        assert_eq!(truncate(title, 10, TruncateMode::Word), "A very…");
+        assert_eq!(TruncateMode::from_name("word"), Some(TruncateMode::Word));
+        assert_eq!(TruncateMode::from_name("line"), None);
+    }
+
+    #[test]
+    fn test_sanitize_message_strips_ansi_colors() {
+        assert_eq!(
+            sanitize_message("\x1b[1;31merror\x1b[0m: build failed"),
+            "error: build failed"
+        );
+        assert_eq!(sanitize_message("\x1b[2Kdone"), "done");
+    }
+
+    #[test]
+    fn test_sanitize_message_strips_osc_and_other_escapes() {
+        assert_eq!(sanitize_message("\x1b]0;title\x07done"), "done");
+        assert_eq!(
+            sanitize_message("see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\ now"),
+            "see docs now"
+        );
+        assert_eq!(
+            sanitize_message("\x1b7saved\x1b8 \x1b(Bok\x1b="),
+            "saved ok"
+        );
+        assert_eq!(sanitize_message("line\x1b\nnext"), "line\nnext");
+    }
+
+    #[test]
+    fn test_sanitize_message_strips_control_chars() {
+        assert_eq!(sanitize_message("ding\x07 done\r"), "ding done");
+        assert_eq!(sanitize_message("a\tb\x7f"), "a b");
+    }
+
+    #[test]
+    fn test_sanitize_message_keeps_plain_text() {
+        let text = "Line one\nLine two — café ✅";
+        assert_eq!(sanitize_message(text), text);
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// Width of the message column in the history table.
+const MESSAGE_PREVIEW_CHARS: usize = 60;
+
+/// A single sent notification, stored as one line of the JSONL log.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LogEvent {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Notification id for correlation; absent in logs from older versions.
+    #[serde(default)]
+    pub id: String,
+    pub session_id: String,
+    pub title: String,
+    pub message: String,
+}
+
+impl LogEvent {
+    pub fn new(session_id: &str, title: &str, message: &str, now: SystemTime) -> Self {
+        LogEvent {
+            timestamp: now
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            id: String::new(),
+            session_id: session_id.to_string(),
+            title: title.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Default location of the notification log.
+pub fn default_history_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(HISTORY_FILE_NAME))
+}
+
+/// Appends an event to the JSONL log, creating the file if needed.
+pub fn append_event(path: &Path, event: &LogEvent) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Reads the last `limit` events from the log. Malformed lines are skipped.
+pub fn read_history(path: &Path, limit: usize) -> Result<Vec<LogEvent>> {
+    let file = fs::File::open(path)?;
+
+    let mut events = VecDeque::with_capacity(limit);
+    for line in BufReader::new(file).lines() {
+        let Ok(event) = serde_json::from_str::<LogEvent>(&line?) else {
+            continue;
+        };
+
+        if events.len() == limit {
+            events.pop_front();
+        }
+        if limit > 0 {
+            events.push_back(event);
+        }
+    }
+
+    Ok(events.into())
+}
+
+/// Renders events as a table of timestamp, title, and message preview.
+pub fn format_table(events: &[LogEvent]) -> String {
+    let rows: Vec<[String; 3]> = events
+        .iter()
+        .map(|event| {
+            let timestamp = DateTime::from_timestamp(event.timestamp as i64, 0)
+                .map(|t| {
+                    t.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_else(|| event.timestamp.to_string());
+            [timestamp, event.title.clone(), preview(&event.message)]
+        })
+        .collect();
+
+    let title_width = rows
+        .iter()
+        .map(|row| row[1].chars().count())
+        .chain(std::iter::once("TITLE".len()))
+        .max()
+        .unwrap_or_default();
+
+    std::iter::once([
+        "TIMESTAMP".to_string(),
+        "TITLE".to_string(),
+        "MESSAGE".to_string(),
+    ])
+    .chain(rows)
+    .map(|[timestamp, title, message]| {
+        format!("{:<19}  {:<title_width$}  {}", timestamp, title, message)
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Collapses the message onto one line and shortens it for the table.
+fn preview(message: &str) -> String {
+    let single_line = message.split_whitespace().collect::<Vec<_>>().join(" ");
+    if single_line.chars().count() <= MESSAGE_PREVIEW_CHARS {
+        single_line
+    } else {
+        let truncated: String = single_line
+            .chars()
+            .take(MESSAGE_PREVIEW_CHARS - 1)
+            .collect();
+        format!("{}…", truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE_LOG: &str = r#"{"timestamp":1700000000,"session_id":"a","title":"Claude Code","message":"first"}
+{"timestamp":1700000060,"session_id":"a","title":"Claude Code","message":"second"}
+this is not json
+{"timestamp":1700000120,"session_id":"b","title":"Build","message":"third"}
+"#;
+
+    fn write_sample_log(temp_dir: &TempDir) -> PathBuf {
+        let path = temp_dir.path().join(HISTORY_FILE_NAME);
+        fs::write(&path, SAMPLE_LOG).expect("Failed to write sample log");
+        path
+    }
+
+    #[test]
+    fn test_read_history_skips_malformed_lines() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_sample_log(&temp_dir);
+
+        let events = read_history(&path, 10).unwrap();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn test_read_history_limit_keeps_most_recent() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_sample_log(&temp_dir);
+
+        let events = read_history(&path, 2).unwrap();
+        let messages: Vec<&str> = events.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["second", "third"]);
+
+        assert!(read_history(&path, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_event_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("nested").join(HISTORY_FILE_NAME);
+        let event = LogEvent::new("s", "Claude Code", "hello", UNIX_EPOCH);
+
+        append_event(&path, &event).unwrap();
+        append_event(&path, &event).unwrap();
+
+        assert_eq!(read_history(&path, 10).unwrap(), vec![event.clone(), event]);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let events = vec![LogEvent::new(
+            "s",
+            "Build",
+            "line one\nline two",
+            UNIX_EPOCH,
+        )];
+        let table = format_table(&events);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("TIMESTAMP"));
+        assert!(lines[1].ends_with("Build  line one line two"));
+    }
+
+    #[test]
+    fn test_preview_truncates_long_messages() {
+        let long = "x".repeat(100);
+        assert_eq!(preview(&long).chars().count(), MESSAGE_PREVIEW_CHARS);
+        assert!(preview(&long).ends_with('…'));
+    }
+}
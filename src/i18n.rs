@@ -0,0 +1,128 @@
+/// The locale used when no translation is available.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Translations keyed by language code, then by message key. Every key in
+/// the English table should exist in the others; missing ones fall back to
+/// English.
+const CATALOG: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("title", "Claude Code"),
+            ("stop", "Claude finished"),
+            ("subagent_stop", "Claude subagent finished"),
+            ("notification", "Claude needs input"),
+            ("generic", "Claude Code notification"),
+        ],
+    ),
+    (
+        "de",
+        &[
+            ("title", "Claude Code"),
+            ("stop", "Claude ist fertig"),
+            ("subagent_stop", "Claude-Subagent ist fertig"),
+            ("notification", "Claude benötigt eine Eingabe"),
+            ("generic", "Claude Code-Benachrichtigung"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("title", "Claude Code"),
+            ("stop", "Claude ha terminado"),
+            ("subagent_stop", "El subagente de Claude ha terminado"),
+            ("notification", "Claude necesita tu respuesta"),
+            ("generic", "Notificación de Claude Code"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("title", "Claude Code"),
+            ("stop", "Claude a terminé"),
+            ("subagent_stop", "Le sous-agent de Claude a terminé"),
+            ("notification", "Claude attend une réponse"),
+            ("generic", "Notification de Claude Code"),
+        ],
+    ),
+];
+
+fn lookup(key: &str, language: &str) -> Option<&'static str> {
+    CATALOG
+        .iter()
+        .find(|(lang, _)| *lang == language)?
+        .1
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, text)| *text)
+}
+
+/// Reduces a POSIX locale such as `de_DE.UTF-8` to its language code.
+fn language_of(locale: &str) -> String {
+    locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Returns the translation of `key` for `locale`, falling back to English,
+/// and to the key itself if no translation exists at all.
+pub fn localized<'a>(key: &'a str, locale: &str) -> &'a str {
+    lookup(key, &language_of(locale))
+        .or_else(|| lookup(key, DEFAULT_LOCALE))
+        .unwrap_or(key)
+}
+
+/// Reads the message locale from `LC_ALL`, `LC_MESSAGES`, then `LANG`,
+/// skipping unset or empty values.
+pub fn locale_from_env<F: Fn(&str) -> Option<String>>(lookup: F) -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| lookup(var).filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// The catalog key for the default message of a hook event.
+pub fn event_message_key(event: &str) -> &'static str {
+    match event {
+        "Stop" => "stop",
+        "SubagentStop" => "subagent_stop",
+        "Notification" => "notification",
+        _ => "generic",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized_present_translation() {
+        assert_eq!(localized("stop", "de_DE.UTF-8"), "Claude ist fertig");
+        assert_eq!(localized("notification", "fr"), "Claude attend une réponse");
+    }
+
+    #[test]
+    fn test_localized_missing_locale_falls_back_to_english() {
+        assert_eq!(localized("stop", "ja_JP.UTF-8"), "Claude finished");
+        assert_eq!(localized("stop", "C"), "Claude finished");
+    }
+
+    #[test]
+    fn test_localized_missing_key() {
+        assert_eq!(localized("no_such_key", "de"), "no_such_key");
+    }
+
+    #[test]
+    fn test_locale_from_env_precedence() {
+        let locale = locale_from_env(|var| match var {
+            "LC_MESSAGES" => Some("es_ES.UTF-8".to_string()),
+            "LANG" => Some("de_DE.UTF-8".to_string()),
+            _ => None,
+        });
+        assert_eq!(locale, "es_ES.UTF-8");
+
+        assert_eq!(locale_from_env(|_| None), "en");
+    }
+}
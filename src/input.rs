@@ -0,0 +1,540 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+#[cfg(feature = "schema")]
+use std::sync::OnceLock;
+
+use crate::{format, NotificationError, NotificationInput, NotificationResult};
+
+/// The JSON type each input field must have, and whether it is required.
+const INPUT_FIELDS: &[(&str, FieldKind, bool)] = &[
+    ("session_id", FieldKind::String, true),
+    ("transcript_path", FieldKind::String, true),
+    ("message", FieldKind::String, false),
+    ("title", FieldKind::String, false),
+    ("hook_event_name", FieldKind::String, false),
+    ("stop_hook_active", FieldKind::Bool, false),
+    ("timeout_ms", FieldKind::Integer, false),
+];
+
+/// JSON Schema for the hook payload, checked with `--validate-input`.
+#[cfg(feature = "schema")]
+const INPUT_SCHEMA: &str = include_str!("input_schema.json");
+
+/// Longest excerpt of the input shown in an error message.
+const SNIPPET_LEN: usize = 40;
+
+#[derive(Clone, Copy)]
+enum FieldKind {
+    String,
+    Bool,
+    Integer,
+}
+
+impl FieldKind {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            FieldKind::String => value.is_string(),
+            FieldKind::Bool => value.is_boolean(),
+            FieldKind::Integer => value.as_u64().is_some_and(|n| n <= u64::from(u32::MAX)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            FieldKind::String => "a string",
+            FieldKind::Bool => "a boolean",
+            FieldKind::Integer => "a non-negative integer",
+        }
+    }
+}
+
+/// Default cap on how much input is read from stdin (1 MiB).
+pub const DEFAULT_MAX_INPUT_BYTES: usize = 1024 * 1024;
+
+/// Opens the input source: the file at `path` if given, otherwise stdin.
+pub fn open_input(path: Option<&Path>) -> Result<Box<dyn Read>> {
+    match path {
+        Some(path) => {
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open input file {}", path.display()))?;
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdin())),
+    }
+}
+
+/// Reads all input as bytes and decodes it as UTF-8, replacing invalid
+/// sequences with U+FFFD so a single bad byte doesn't fail the whole read.
+pub fn read_stdin_lossy<R: Read>(mut r: R) -> Result<String> {
+    let mut bytes = Vec::new();
+    r.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Like [`read_stdin_lossy`], but reads at most `max` bytes and fails if the
+/// input is longer, so a runaway producer can't exhaust memory.
+pub fn read_capped<R: Read>(r: R, max: usize) -> Result<String> {
+    // Read one extra byte to tell "exactly at the cap" from "over it"
+    let mut bytes = Vec::new();
+    r.take((max as u64).saturating_add(1))
+        .read_to_end(&mut bytes)?;
+    if bytes.len() > max {
+        anyhow::bail!(
+            "Input exceeds {} bytes; raise the limit with --max-input-bytes",
+            max
+        );
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Parses and validates a JSON notification payload, reporting which field
+/// is missing or malformed along with an excerpt of the input.
+pub fn parse_input(s: &str) -> NotificationResult<NotificationInput> {
+    input_from_json(parse_json(s)?)
+}
+
+/// Parses JSON text, reporting syntax errors with their position and an
+/// excerpt of the input around it.
+pub fn parse_json(s: &str) -> NotificationResult<Value> {
+    serde_json::from_str(s).map_err(|e| json_syntax_error(s, &e))
+}
+
+/// Converts a JSON error into an `InvalidInput` error pointing at the
+/// offending position in `s`.
+pub fn json_syntax_error(s: &str, e: &serde_json::Error) -> NotificationError {
+    let line = s
+        .lines()
+        .nth(e.line().saturating_sub(1))
+        .unwrap_or_default();
+    let start = e.column().saturating_sub(SNIPPET_LEN / 2);
+    let near: String = line.chars().skip(start).take(SNIPPET_LEN).collect();
+
+    NotificationError::invalid_input(format!(
+        "invalid JSON at line {}, column {} near `{}`",
+        e.line(),
+        e.column(),
+        near.trim()
+    ))
+}
+
+/// Builds an input from plain text used as the whole message, for `--raw`.
+/// There's no session or transcript, so those are left empty.
+pub fn raw_input(text: &str) -> NotificationInput {
+    NotificationInput {
+        session_id: String::new(),
+        transcript_path: String::new(),
+        message: text.trim_end().to_string(),
+        title: None,
+        hook_event_name: None,
+        stop_hook_active: None,
+        timeout_ms: None,
+    }
+}
+
+/// Converts a parsed JSON value into an input, checking each known field
+/// first so errors name the field rather than a position.
+pub fn input_from_json(value: Value) -> NotificationResult<NotificationInput> {
+    let Some(object) = value.as_object() else {
+        return Err(NotificationError::invalid_input(format!(
+            "expected a JSON object, got `{}`",
+            snippet(&value)
+        )));
+    };
+
+    for (field, kind, required) in INPUT_FIELDS {
+        match object.get(*field) {
+            None if *required => {
+                return Err(NotificationError::invalid_input(format!(
+                    "missing required field `{}`",
+                    field
+                )));
+            }
+            Some(Value::Null) if !required => {}
+            Some(v) if !kind.accepts(v) => {
+                return Err(NotificationError::invalid_input(format!(
+                    "field `{}` must be {}, got `{}`",
+                    field,
+                    kind.name(),
+                    snippet(v)
+                )));
+            }
+            _ => {}
+        }
+    }
+
+    serde_json::from_value(value).map_err(|e| NotificationError::invalid_input(e.to_string()))
+}
+
+/// Checks a payload against the bundled JSON Schema, reporting every
+/// violation with the path of the offending value.
+#[cfg(feature = "schema")]
+pub fn validate_input_against_schema(v: &Value) -> NotificationResult<()> {
+    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+    let validator = VALIDATOR.get_or_init(|| {
+        let schema = serde_json::from_str(INPUT_SCHEMA).expect("bundled input schema is JSON");
+        jsonschema::validator_for(&schema).expect("bundled input schema is valid")
+    });
+
+    let errors: Vec<String> = validator
+        .iter_errors(v)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            format!("{}: {}", if path.is_empty() { "/" } else { &path }, e)
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    Err(NotificationError::invalid_input(format!(
+        "schema validation failed: {}",
+        errors.join("; ")
+    )))
+}
+
+/// Renders a JSON value compactly, truncated to `SNIPPET_LEN` characters.
+fn snippet(value: &Value) -> String {
+    format::truncate_chars(&value.to_string(), SNIPPET_LEN)
+}
+
+/// Most messages listed in a coalesced notification; the rest are counted.
+const MAX_COALESCED_MESSAGES: usize = 5;
+
+/// Combines several inputs into one `(title, body)`, where `title` is the
+/// already resolved notification title. The body lists the first line of
+/// each message as a bullet, up to `MAX_COALESCED_MESSAGES`. A single input
+/// keeps its own title, falling back to `title`, and its message.
+pub fn coalesce_inputs(inputs: &[NotificationInput], title: &str) -> (String, String) {
+    if let [input] = inputs {
+        let title = input.title.as_deref().unwrap_or(title);
+        return (title.to_string(), input.message.clone());
+    }
+
+    let mut lines: Vec<String> = inputs
+        .iter()
+        .take(MAX_COALESCED_MESSAGES)
+        .map(|input| format!("• {}", format::first_n_lines(input.message.trim(), 1)))
+        .collect();
+    if inputs.len() > MAX_COALESCED_MESSAGES {
+        lines.push(format!(
+            "…and {} more",
+            inputs.len() - MAX_COALESCED_MESSAGES
+        ));
+    }
+
+    (
+        format!("{} ({} updates)", title, inputs.len()),
+        lines.join("\n"),
+    )
+}
+
+/// Maps `NotificationInput` field names to the keys used by a nonstandard
+/// producer. Unset entries keep the default field name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldMap {
+    pub session_id: Option<String>,
+    pub transcript_path: Option<String>,
+    pub message: Option<String>,
+    pub title: Option<String>,
+}
+
+impl FieldMap {
+    fn entries(&self) -> [(&'static str, Option<&str>); 4] {
+        [
+            ("session_id", self.session_id.as_deref()),
+            ("transcript_path", self.transcript_path.as_deref()),
+            ("message", self.message.as_deref()),
+            ("title", self.title.as_deref()),
+        ]
+    }
+}
+
+/// Renames the mapped keys of a JSON object to the field names expected by
+/// `NotificationInput`. Non-object values are returned unchanged.
+pub fn remap_fields(mut v: Value, mapping: &FieldMap) -> Value {
+    let Some(object) = v.as_object_mut() else {
+        return v;
+    };
+
+    for (field, source) in mapping.entries() {
+        let Some(source) = source.filter(|source| *source != field) else {
+            continue;
+        };
+
+        match object.remove(source) {
+            Some(value) => {
+                object.insert(field.to_string(), value);
+            }
+            None => {
+                // Don't let a stray default-named key satisfy a remapped field
+                object.remove(field);
+            }
+        }
+    }
+
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_TITLE;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_stdin_lossy_invalid_utf8() {
+        let mut bytes = br#"{"session_id": "s", "transcript_path": "/tmp/"#.to_vec();
+        bytes.extend_from_slice(&[0xff, 0xfe]);
+        bytes.extend_from_slice(br#".md", "message": "hi"}"#);
+
+        let text = read_stdin_lossy(Cursor::new(bytes)).unwrap();
+        let value: Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(value["transcript_path"], "/tmp/\u{fffd}\u{fffd}.md");
+        assert_eq!(value["message"], "hi");
+    }
+
+    #[test]
+    fn test_read_stdin_lossy_valid_utf8() {
+        let text = read_stdin_lossy(Cursor::new("héllo ✅")).unwrap();
+        assert_eq!(text, "héllo ✅");
+    }
+
+    #[test]
+    fn test_remap_fields_default_names() {
+        let input = json!({
+            "session_id": "abc",
+            "transcript_path": "/tmp/t.md",
+            "message": "hello"
+        });
+
+        assert_eq!(remap_fields(input.clone(), &FieldMap::default()), input);
+    }
+
+    #[test]
+    fn test_remap_fields_custom_names() {
+        let mapping = FieldMap {
+            message: Some("text".to_string()),
+            title: Some("heading".to_string()),
+            ..Default::default()
+        };
+        let input = json!({
+            "session_id": "abc",
+            "transcript_path": "/tmp/t.md",
+            "text": "hello",
+            "heading": "Build"
+        });
+
+        assert_eq!(
+            remap_fields(input, &mapping),
+            json!({
+                "session_id": "abc",
+                "transcript_path": "/tmp/t.md",
+                "message": "hello",
+                "title": "Build"
+            })
+        );
+    }
+
+    #[test]
+    fn test_remap_fields_non_object() {
+        let mapping = FieldMap {
+            message: Some("text".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(remap_fields(json!([1, 2]), &mapping), json!([1, 2]));
+    }
+
+    #[test]
+    fn test_read_capped_under_limit() {
+        assert_eq!(read_capped(Cursor::new("hello"), 10).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_read_capped_at_limit() {
+        assert_eq!(read_capped(Cursor::new("hello"), 5).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_read_capped_over_limit() {
+        let err = read_capped(Cursor::new("hello!"), 5).unwrap_err();
+        assert!(err.to_string().contains("exceeds 5 bytes"));
+    }
+
+    #[test]
+    fn test_read_capped_max_limit() {
+        assert_eq!(
+            read_capped(Cursor::new("hello"), usize::MAX).unwrap(),
+            "hello"
+        );
+    }
+
+    fn input(message: &str) -> NotificationInput {
+        NotificationInput {
+            session_id: "abc123".to_string(),
+            transcript_path: "/tmp/transcript.jsonl".to_string(),
+            message: message.to_string(),
+            title: None,
+            hook_event_name: None,
+            stop_hook_active: None,
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_coalesce_single_input() {
+        let (title, body) = coalesce_inputs(&[input("Build finished")], DEFAULT_TITLE);
+
+        assert_eq!(title, "Claude Code");
+        assert_eq!(body, "Build finished");
+    }
+
+    #[test]
+    fn test_coalesce_two_inputs() {
+        let (title, body) = coalesce_inputs(
+            &[input("Build finished"), input("Tests\npassed")],
+            DEFAULT_TITLE,
+        );
+
+        assert_eq!(title, "Claude Code (2 updates)");
+        assert_eq!(body, "• Build finished\n• Tests…");
+    }
+
+    #[test]
+    fn test_coalesce_many_inputs() {
+        let inputs: Vec<_> = (1..=8).map(|i| input(&format!("Task {}", i))).collect();
+        let (title, body) = coalesce_inputs(&inputs, "Build");
+
+        assert_eq!(title, "Build (8 updates)");
+        assert_eq!(
+            body,
+            "• Task 1\n• Task 2\n• Task 3\n• Task 4\n• Task 5\n…and 3 more"
+        );
+    }
+
+    #[test]
+    fn test_open_input_from_file() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("event.json");
+        std::fs::write(
+            &path,
+            r#"{"session_id": "abc123", "transcript_path": "/tmp/t.jsonl", "message": "Replayed"}"#,
+        )
+        .unwrap();
+
+        let text = read_stdin_lossy(open_input(Some(&path)).unwrap()).unwrap();
+        let input: NotificationInput = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(input.session_id, "abc123");
+        assert_eq!(input.message, "Replayed");
+    }
+
+    #[test]
+    fn test_open_input_missing_file() {
+        let err = open_input(Some(Path::new("/nonexistent/event.json")))
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .contains("Failed to open input file /nonexistent/event.json"));
+    }
+
+    #[test]
+    fn test_parse_input_missing_message_uses_default() {
+        // `message` is optional; `normalize` fills in an event default
+        let input = parse_input(r#"{"session_id": "s", "transcript_path": "/t"}"#).unwrap();
+        assert_eq!(input.message, "");
+    }
+
+    #[test]
+    fn test_parse_input_missing_session_id() {
+        let err = parse_input(r#"{"transcript_path": "/t", "message": "hi"}"#).unwrap_err();
+
+        assert!(matches!(err, NotificationError::InvalidInput(_)));
+        assert!(err
+            .to_string()
+            .contains("missing required field `session_id`"));
+    }
+
+    #[test]
+    fn test_parse_input_type_mismatch() {
+        let err = parse_input(r#"{"session_id": 42, "transcript_path": "/t", "message": "hi"}"#)
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("field `session_id` must be a string, got `42`"));
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_input_against_schema_valid() {
+        let payload = serde_json::json!({
+            "session_id": "abc123",
+            "transcript_path": "/t",
+            "message": "Claude needs input",
+            "hook_event_name": "Notification",
+            "timeout_ms": 5000,
+            "cwd": "/project"
+        });
+
+        assert!(validate_input_against_schema(&payload).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_input_against_schema_wrong_types() {
+        let payload = serde_json::json!({
+            "session_id": "abc123",
+            "transcript_path": "/t",
+            "stop_hook_active": "yes",
+            "timeout_ms": -1
+        });
+
+        let message = validate_input_against_schema(&payload)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("/stop_hook_active"));
+        assert!(message.contains("/timeout_ms"));
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn test_validate_input_against_schema_missing_required() {
+        let payload = serde_json::json!({"message": "hi"});
+
+        let message = validate_input_against_schema(&payload)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("session_id"));
+        assert!(message.contains("transcript_path"));
+        assert!(validate_input_against_schema(&serde_json::json!([1])).is_err());
+        assert!(validate_input_against_schema(
+            &serde_json::json!({"session_id": "", "transcript_path": "/t"})
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_raw_input() {
+        let input = raw_input("make: *** [all] Error 2\n\n");
+
+        assert_eq!(input.message, "make: *** [all] Error 2");
+        assert_eq!(input.title, None);
+        assert!(input.session_id.is_empty());
+    }
+
+    #[test]
+    fn test_parse_input_syntax_error_shows_snippet() {
+        let err = parse_input(r#"{"session_id": "s", "message": oops}"#).unwrap_err();
+        let message = err.to_string();
+
+        assert!(message.contains("line 1, column"));
+        assert!(message.contains("oops"));
+    }
+}
@@ -1,23 +1,95 @@
+pub mod backend;
+#[cfg(feature = "card")]
+pub mod card;
+pub mod classify;
+pub mod clipboard;
+pub mod config;
+pub mod embedded;
 pub mod error;
+pub mod format;
+pub mod history;
+pub mod i18n;
+pub mod input;
+pub mod notifier;
+pub mod output;
+pub mod paths;
+pub mod push;
+pub mod quiet;
+pub mod remote;
+pub mod reply;
+pub mod sessions;
+pub mod sink;
+pub mod spool;
+pub mod state;
+pub mod syslog;
+pub mod transcript;
+pub mod warn;
+pub mod webhook;
 
-use anyhow::Result;
-use notify_rust::Notification;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Read;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 pub use error::{NotificationError, NotificationResult};
+pub use input::FieldMap;
+pub use notifier::{Notifier, PreparedNotification};
+pub use sink::{NotificationSink, RecordingSink, SystemSink};
+pub use warn::{Diagnostics, WarningSink};
+
+/// Title used when neither the input nor the options provide one.
+pub const DEFAULT_TITLE: &str = "Claude Code";
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct NotificationInput {
     pub session_id: String,
     pub transcript_path: String,
+    /// Absent from some payloads (e.g. `Stop`); see [`NotificationInput::normalize`].
+    #[serde(default)]
     pub message: String,
     pub title: Option<String>,
+    #[serde(default)]
+    pub hook_event_name: Option<String>,
+    #[serde(default)]
+    pub stop_hook_active: Option<bool>,
+    /// How long this notification stays up, overriding `--timeout`.
+    #[serde(default)]
+    pub timeout_ms: Option<u32>,
 }
 
-#[derive(Debug, Clone, Default)]
+impl NotificationInput {
+    /// Fills in a message for payloads that don't carry one (or only carry
+    /// whitespace), based on the hook event that produced them.
+    pub fn normalize(self) -> NotificationInput {
+        self.normalize_in(i18n::DEFAULT_LOCALE)
+    }
+
+    /// Like [`NotificationInput::normalize`], with the default message
+    /// translated for `locale`.
+    pub fn normalize_in(mut self, locale: &str) -> NotificationInput {
+        self.message = self.message_in(locale).to_string();
+        self
+    }
+
+    /// The message to show: the input's own, or the default for its event
+    /// translated for `locale`.
+    pub fn message_in(&self, locale: &str) -> &str {
+        if self.message.trim().is_empty() {
+            let event = self.hook_event_name.as_deref().unwrap_or_default();
+            i18n::localized(i18n::event_message_key(event), locale)
+        } else {
+            &self.message
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum Sound {
     #[default]
     Glass,
@@ -34,6 +106,9 @@ pub enum Sound {
     Pop,
     Sosumi,
     Tink,
+    /// The OS's default notification sound, played by the notification
+    /// itself rather than a player.
+    SystemDefault,
     Custom(String),
 }
 
@@ -54,6 +129,7 @@ impl Sound {
             "Pop" => Sound::Pop,
             "Sosumi" => Sound::Sosumi,
             "Tink" => Sound::Tink,
+            "Default" => Sound::SystemDefault,
             custom => Sound::Custom(custom.to_string()),
         }
     }
@@ -74,224 +150,2482 @@ impl Sound {
             Sound::Pop => "Pop",
             Sound::Sosumi => "Sosumi",
             Sound::Tink => "Tink",
+            Sound::SystemDefault => "Default",
             Sound::Custom(name) => name,
         }
     }
 
-    pub fn get_afplay_path(&self) -> String {
+    /// Resolves the sound to a file path. Bare names are looked up as
+    /// `<base_dir>/<name>.aiff`; names containing a slash are used as-is.
+    pub fn get_afplay_path(&self, base_dir: &Path) -> String {
         let sound_name = self.as_str();
 
         // If the sound name contains a slash, treat it as a custom path
         if sound_name.contains('/') {
             sound_name.to_string()
         } else {
-            // System sound - add path prefix and .aiff extension
-            format!("/System/Library/Sounds/{}.aiff", sound_name)
+            // Bare name - add the base directory and .aiff extension
+            base_dir
+                .join(format!("{}.aiff", sound_name))
+                .display()
+                .to_string()
         }
     }
 }
 
-pub fn main<R: Read>(mut stdin: R, sound: Sound) -> Result<()> {
-    // Read all input from stdin
-    let mut buffer = String::new();
-    stdin.read_to_string(&mut buffer)?;
+/// File extensions `afplay` can play.
+const SUPPORTED_AUDIO_EXTENSIONS: &[&str] = &["aiff", "wav", "mp3", "m4a", "caf"];
 
-    // Parse the JSON input
-    let input: NotificationInput = serde_json::from_str(&buffer)?;
+/// Returns true when the path has an extension `afplay` supports
+/// (case-insensitive). Paths without an extension are not supported.
+pub fn is_supported_audio(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            SUPPORTED_AUDIO_EXTENSIONS
+                .iter()
+                .any(|supported| ext.eq_ignore_ascii_case(supported))
+        })
+}
 
-    // Create and send the notification
-    send_notification(&input, &sound)?;
+/// Reads a sound name or path from a file or FIFO written by another
+/// process. Surrounding whitespace is trimmed; the name must be a single
+/// non-empty line.
+pub fn read_sound_name(path: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read sound name from {}", path.display()))?;
+    let name = content.trim();
+    if name.is_empty() {
+        anyhow::bail!("{} does not contain a sound name", path.display());
+    }
+    if name.contains('\n') {
+        anyhow::bail!(
+            "{} contains more than one line; expected a single sound name",
+            path.display()
+        );
+    }
+    Ok(name.to_string())
+}
+
+/// Warns, without failing, when afplay is unlikely to play the file.
+fn warn_if_unsupported_audio(sound_path: &str, warnings: &WarningSink) {
+    if !is_supported_audio(sound_path) {
+        warnings.warn(&format!(
+            "'{}' may not be playable; afplay supports {}",
+            sound_path,
+            SUPPORTED_AUDIO_EXTENSIONS
+                .iter()
+                .map(|ext| format!(".{}", ext))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+}
+
+/// Per-sound volume multipliers, keyed by sound name.
+pub type VolumeTable = HashMap<String, f32>;
+
+/// Computes the playback volume for a sound: the per-sound multiplier from
+/// the table (1.0 if absent) scaled by the CLI `--volume`, if given.
+pub fn effective_volume(sound: &Sound, cli: Option<f32>, table: &VolumeTable) -> f32 {
+    let per_sound = table.get(sound.as_str()).copied().unwrap_or(1.0);
+    per_sound * cli.unwrap_or(1.0)
+}
+
+/// Sound names keyed by hook event name (the `[event_sounds]` table).
+pub type EventSounds = HashMap<String, String>;
+
+/// Picks the sound configured for the hook `event`, or `default` when there
+/// is no event or the table has no entry for it.
+pub fn sound_for_event(event: Option<&str>, table: &EventSounds, default: &Sound) -> Sound {
+    event
+        .and_then(|event| table.get(event))
+        .map(|name| Sound::from_name(name))
+        .unwrap_or_else(|| default.clone())
+}
+
+/// Picks the notification timeout: the input's own `timeout_ms` wins over
+/// the `--timeout` default.
+pub fn effective_timeout(input: Option<u32>, cli: Option<u32>) -> Option<u32> {
+    input.or(cli)
+}
+
+/// Upper bound on `--repeat` so a typo can't loop the sound for minutes.
+pub const MAX_REPEAT: u32 = 10;
+
+/// Pause between repeated plays of the sound.
+const REPEAT_GAP: Duration = Duration::from_millis(250);
+
+/// Where bare sound names are looked up unless overridden.
+pub const SYSTEM_SOUNDS_DIR: &str = "/System/Library/Sounds";
+
+/// The command used to play sounds unless overridden.
+pub const DEFAULT_PLAYER: &str = "afplay";
 
+/// How `afplay` is invoked for a sound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playback {
+    /// The player command, `afplay` or a compatible wrapper.
+    pub player: String,
+    /// Directory that bare sound names resolve against.
+    pub sound_dir: PathBuf,
+    /// Volume multiplier passed to `afplay -v` (1.0 is the file's own level).
+    pub volume: f32,
+    /// How many times to play the sound, clamped to `1..=MAX_REPEAT`.
+    pub repeat: u32,
+    /// Extra arguments from `--player-args`, passed before the sound path.
+    pub extra_args: Vec<String>,
+    /// Played instead when the sound's file doesn't exist.
+    pub fallback: Sound,
+    /// Receives warnings about sounds that can't be played as asked.
+    pub warnings: WarningSink,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback {
+            player: DEFAULT_PLAYER.to_string(),
+            sound_dir: PathBuf::from(SYSTEM_SOUNDS_DIR),
+            volume: 1.0,
+            repeat: 1,
+            extra_args: Vec::new(),
+            fallback: Sound::default(),
+            warnings: WarningSink::default(),
+        }
+    }
+}
+
+/// Clamps a requested repeat count to `1..=MAX_REPEAT`.
+fn repeat_count(requested: u32) -> u32 {
+    requested.clamp(1, MAX_REPEAT)
+}
+
+/// Calls `play_once` `times` times, pausing `gap` between calls and stopping
+/// at the first failure.
+fn play_repeated<F: FnMut() -> Result<()>>(
+    times: u32,
+    gap: Duration,
+    mut play_once: F,
+) -> Result<()> {
+    for i in 0..times {
+        if i > 0 {
+            thread::sleep(gap);
+        }
+        play_once()?;
+    }
     Ok(())
 }
 
-fn send_notification(input: &NotificationInput, sound: &Sound) -> Result<()> {
-    let title = input.title.as_deref().unwrap_or("Claude Code");
+/// Safety cap on how many times a looping sound plays if the notification
+/// is never dismissed.
+pub const MAX_LOOP_PLAYS: u32 = 60;
+
+/// Calls `play_once` until `cancel` is set, at most `max_plays` times,
+/// pausing `gap` between calls. Returns how many times it played, or the
+/// first failure.
+fn play_until_cancelled<F: FnMut() -> Result<()>>(
+    cancel: &AtomicBool,
+    max_plays: u32,
+    gap: Duration,
+    mut play_once: F,
+) -> Result<u32> {
+    let mut plays = 0;
+    while plays < max_plays && !cancel.load(Ordering::Relaxed) {
+        if plays > 0 {
+            thread::sleep(gap);
+        }
+        play_once()?;
+        plays += 1;
+    }
+    Ok(plays)
+}
+
+/// Loops the sound until `cancel` is set (e.g. when the notification is
+/// dismissed), capped at `MAX_LOOP_PLAYS`.
+pub(crate) fn loop_sound(sound: &Sound, playback: &Playback, cancel: &AtomicBool) -> Result<()> {
+    play_until_cancelled(cancel, MAX_LOOP_PLAYS, REPEAT_GAP, || {
+        play_sound(
+            sound,
+            &Playback {
+                repeat: 1,
+                ..playback.clone()
+            },
+        )
+        .map(|_| ())
+    })
+    .map(|_| ())
+}
+
+/// Controls when the notification sound is played.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundPolicy {
+    /// Play the sound for every notification.
+    #[default]
+    Always,
+    /// Only play the sound when the message indicates a failure.
+    Error,
+    /// Never play the sound.
+    Never,
+}
 
-    // Clone the sound for the thread
-    let sound_clone = sound.clone();
+impl SoundPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(SoundPolicy::Always),
+            "error" => Some(SoundPolicy::Error),
+            "never" => Some(SoundPolicy::Never),
+            _ => None,
+        }
+    }
 
-    // Spawn a thread to play the sound in parallel
-    let sound_handle = thread::spawn(move || {
-        if let Err(e) = play_sound(&sound_clone) {
-            eprintln!("Warning: Failed to play sound: {}", e);
+    pub fn as_str(&self) -> &str {
+        match self {
+            SoundPolicy::Always => "always",
+            SoundPolicy::Error => "error",
+            SoundPolicy::Never => "never",
         }
-    });
+    }
+}
 
-    // Show the notification (this happens in parallel with sound)
-    let notification_result = Notification::new()
-        .summary(title)
-        .body(&input.message)
-        .show();
+/// Controls when the sound plays relative to showing the notification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SoundTiming {
+    /// Play the sound on a separate thread while the notification is shown.
+    #[default]
+    Parallel,
+    /// Play the sound to completion, then show the notification.
+    Before,
+    /// Show the notification, then play the sound to completion.
+    After,
+    /// Start the player as a detached process and return without waiting
+    /// for the sound to finish.
+    Detached,
+}
 
-    // Wait for the sound thread to complete
-    if let Err(e) = sound_handle.join() {
-        eprintln!("Warning: Sound thread panicked: {:?}", e);
+impl SoundTiming {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "parallel" => Some(SoundTiming::Parallel),
+            "before" => Some(SoundTiming::Before),
+            "after" => Some(SoundTiming::After),
+            "detached" => Some(SoundTiming::Detached),
+            _ => None,
+        }
     }
+}
 
-    // Return the notification result
-    notification_result?;
-    Ok(())
+/// Decides the exit status from whether the notification and the sound
+/// were delivered, for `--on-failure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Fail if either the notification or the sound failed.
+    FailAny,
+    /// Fail only if both failed.
+    FailAll,
+    /// Never fail; failures are only warned about.
+    Never,
 }
 
-fn play_sound(sound: &Sound) -> Result<()> {
-    let sound_path = sound.get_afplay_path();
+impl FailurePolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fail-any" => Some(FailurePolicy::FailAny),
+            "fail-all" => Some(FailurePolicy::FailAll),
+            "never" => Some(FailurePolicy::Never),
+            _ => None,
+        }
+    }
+}
 
-    // Execute afplay command to play the sound
-    let output = Command::new("afplay").arg(&sound_path).output();
+/// The exit code `policy` gives for a delivery where the notification and
+/// sound succeeded or not.
+pub fn compute_exit(notif_ok: bool, sound_ok: bool, policy: FailurePolicy) -> i32 {
+    let failed = match policy {
+        FailurePolicy::FailAny => !notif_ok || !sound_ok,
+        FailurePolicy::FailAll => !notif_ok && !sound_ok,
+        FailurePolicy::Never => false,
+    };
+    i32::from(failed)
+}
 
-    match output {
-        Ok(result) => {
-            if !result.status.success() {
-                // Log a warning but don't fail the whole notification
-                eprintln!(
-                    "Warning: Failed to play sound '{}'. afplay exit code: {:?}",
-                    sound_path,
-                    result.status.code()
-                );
+/// Options controlling how a notification is displayed.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationOptions {
+    pub sound: Sound,
+    pub sound_policy: SoundPolicy,
+    pub sound_timing: SoundTiming,
+    /// Volume multiplier from `--volume`, applied on top of `volumes`.
+    pub volume: Option<f32>,
+    /// Per-sound volume multipliers from the config file.
+    pub volumes: VolumeTable,
+    /// Sounds chosen by session id pattern; the first match replaces `sound`.
+    pub session_sounds: sessions::SessionSounds,
+    /// Sounds by hook event; a session match still takes precedence.
+    pub event_sounds: EventSounds,
+    /// How many times to play the sound (0 is treated as 1).
+    pub repeat: u32,
+    /// Player command from `--player-bin`; defaults to `DEFAULT_PLAYER`.
+    pub player: Option<String>,
+    /// Extra player arguments from `--player-args`.
+    pub player_args: Vec<String>,
+    /// Sound played when the chosen sound's file is missing.
+    pub sound_fallback: Sound,
+    /// Receives non-fatal warnings; prints them to stderr by default.
+    pub warnings: WarningSink,
+    /// Step-by-step diagnostics from `--verbose`; off by default.
+    pub diagnostics: Diagnostics,
+    /// Base directory for bare sound names; defaults to `SYSTEM_SOUNDS_DIR`.
+    pub sound_dir: Option<PathBuf>,
+    /// Title to use when the input doesn't provide one.
+    pub title: Option<String>,
+    /// Longest title in characters before it's cut with "…" (0 disables).
+    pub title_max_len: usize,
+    /// Whether long titles may be cut mid-word.
+    pub truncate_mode: format::TruncateMode,
+    pub strip_markdown: bool,
+    /// Leave ANSI escapes and control characters in the message instead of
+    /// removing them.
+    pub keep_control_chars: bool,
+    /// Column to hard-wrap the body at (0 disables wrapping).
+    pub max_width: usize,
+    /// Keep only this many non-empty lines of the message (0 keeps all).
+    pub summary_lines: usize,
+    /// Treat sound playback failures as errors instead of warnings.
+    pub strict_sound: bool,
+    /// Notify even if notifications were turned off with `disable`.
+    pub force: bool,
+    /// Decide the result from both the notification and the sound, instead
+    /// of failing on the notification alone (or the sound with
+    /// `strict_sound`).
+    pub on_failure: Option<FailurePolicy>,
+    /// The Claude Code hook event that triggered this notification.
+    pub hook_event: Option<String>,
+    /// Renames nonstandard input keys before parsing.
+    pub field_map: FieldMap,
+    /// Check input against the bundled JSON Schema before parsing it.
+    pub validate_input: bool,
+    /// When the task started, used to append the elapsed time to the body.
+    pub started_at: Option<SystemTime>,
+    /// Append the elapsed time, using the transcript's creation time when
+    /// `started_at` isn't set.
+    pub show_elapsed: bool,
+    /// Append the crate version to the body.
+    pub show_version: bool,
+    /// Print the notification id to stdout after sending.
+    pub emit_id: bool,
+    /// Also write each sent notification to the system log.
+    pub syslog: bool,
+    /// Count notifications in a persistent counter shown after the message
+    /// text, e.g. "(3 unread)", since no backend can set a real badge.
+    pub count_badge: bool,
+    /// Append each sent notification to this JSONL log.
+    pub log_file: Option<PathBuf>,
+    /// Show the transcript path below the message in this form.
+    pub transcript_display: Option<format::PathDisplay>,
+    /// Append tool call and turn counts read from the transcript.
+    pub transcript_summary: bool,
+    /// Take `sound:` and `title:` overrides from the transcript's front
+    /// matter.
+    pub transcript_front_matter: bool,
+    /// Only notify for the first event of each session.
+    pub once_per_session: bool,
+    /// Stop notifying once a session has had this many notifications.
+    pub max_per_session: Option<u32>,
+    /// Add how long it's been since the session's previous notification.
+    pub since_last: bool,
+    /// Template the body is rendered from; see [`format::render_body`].
+    pub message_template: Option<String>,
+    /// Also copy the message to the clipboard.
+    pub copy: bool,
+    /// Directory to save a PNG card of each notification in.
+    pub save_card: Option<PathBuf>,
+    /// Base URL the transcripts are served from; adds a QR code linking to
+    /// the transcript to saved cards.
+    pub card_qr_base: Option<String>,
+    /// Skip events without a message instead of showing a default one.
+    pub skip_empty: bool,
+    /// Maximum bytes read from stdin (0 reads without a limit).
+    pub max_input_bytes: usize,
+    /// Loop the sound until the notification is dismissed, where supported.
+    pub loop_until_dismissed: bool,
+    /// Combine multiple JSON inputs into a single summary notification.
+    pub coalesce: bool,
+    /// Treat the input as the plain-text message instead of JSON.
+    pub raw: bool,
+    /// Skip messages classified below this severity.
+    pub min_severity: classify::Severity,
+    pub backend: backend::Backend,
+    /// Banner or persistent alert; see `backend::backend_for_style`.
+    pub style: backend::NotificationStyle,
+    /// Freedesktop category hint; defaults to `backend::DEFAULT_CATEGORY`.
+    pub category: Option<String>,
+    /// Extra freedesktop hints from `--hint`.
+    pub hints: Vec<(String, backend::HintValue)>,
+    /// Icon name or path from `--icon`; overrides `icon_from_theme`.
+    pub icon: Option<String>,
+    /// Pick a themed icon from the message's classification.
+    pub icon_from_theme: bool,
+    /// URL to open when the notification is clicked.
+    pub action_url: Option<String>,
+    /// Groups notifications by this key instead of by session.
+    pub group_key: Option<String>,
+    pub format: output::OutputFormat,
+    /// Where notifications are shown instead of the format and backend,
+    /// e.g. a [`RecordingSink`] in tests.
+    pub sink: Option<Arc<dyn NotificationSink>>,
+    /// Print to stdout when no notification server is available.
+    pub fallback_stdout: bool,
+    /// Also ring the terminal bell.
+    pub bell: bool,
+    /// How long notifications stay up in milliseconds, where supported.
+    pub timeout_ms: Option<u32>,
+    /// How long to wait after reading the input before showing it.
+    pub delay: Duration,
+    /// Save notifications that fail to deliver here for a later `flush`.
+    pub spool_dir: Option<PathBuf>,
+    /// Ask for a reply after notifying and append it here, where supported.
+    pub reply_file: Option<PathBuf>,
+    /// Add a macOS reminder instead of showing a notification.
+    pub as_reminder: bool,
+    /// Minutes from now the reminder is due, if any.
+    pub reminder_due: Option<u32>,
+    /// URL to POST the notification to instead of showing it.
+    pub webhook: Option<String>,
+    /// Show the desktop notification in addition to posting the webhook.
+    pub webhook_also: bool,
+    /// Mobile push service that also receives each notification.
+    pub push: Option<push::PushTarget>,
+    /// Locale for the default title and messages; English when unset.
+    pub locale: Option<String>,
+    /// Daily window during which notifications are silenced or suppressed.
+    pub quiet_hours: Option<quiet::QuietHours>,
+}
+
+impl NotificationOptions {
+    /// Prints the notification in the `plain` format instead of showing it,
+    /// turning off everything else it would do: the sound, network
+    /// delivery, the clipboard and other outputs, and the state files.
+    pub fn dry_run(self) -> Self {
+        NotificationOptions {
+            format: output::OutputFormat::Plain,
+            sink: None,
+            fallback_stdout: false,
+            bell: false,
+            sound_policy: SoundPolicy::Never,
+            loop_until_dismissed: false,
+            action_url: None,
+            delay: Duration::ZERO,
+            webhook: None,
+            webhook_also: false,
+            push: None,
+            syslog: false,
+            spool_dir: None,
+            as_reminder: false,
+            reply_file: None,
+            copy: false,
+            save_card: None,
+            log_file: None,
+            count_badge: false,
+            once_per_session: false,
+            max_per_session: None,
+            since_last: false,
+            ..self
+        }
+    }
+
+    /// Options for retrying a spooled notification. Only the desktop
+    /// notification and its sound go out again: the counters, history,
+    /// outputs, and network sends already ran on the first attempt.
+    pub fn replay(self) -> Self {
+        NotificationOptions {
+            delay: Duration::ZERO,
+            webhook: None,
+            webhook_also: false,
+            push: None,
+            syslog: false,
+            spool_dir: None,
+            as_reminder: false,
+            reply_file: None,
+            copy: false,
+            save_card: None,
+            log_file: None,
+            count_badge: false,
+            once_per_session: false,
+            max_per_session: None,
+            since_last: false,
+            ..self
+        }
+    }
+}
+
+/// Returns the message to show when the hook payload has no message of its own.
+pub fn default_message_for_event(event: &str) -> &'static str {
+    i18n::localized(i18n::event_message_key(event), i18n::DEFAULT_LOCALE)
+}
+
+pub fn should_play_sound(message: &str, policy: SoundPolicy) -> bool {
+    match policy {
+        SoundPolicy::Always => true,
+        SoundPolicy::Error => classify::is_error_message(message),
+        SoundPolicy::Never => false,
+    }
+}
+
+pub fn main<R: Read>(stdin: R, sound: Sound) -> Result<()> {
+    let options = NotificationOptions {
+        sound,
+        ..Default::default()
+    };
+
+    run(stdin, &options)
+}
+
+pub fn run<R: Read>(stdin: R, options: &NotificationOptions) -> Result<()> {
+    run_with_suppression(stdin, options).map(|_| ())
+}
+
+/// Like [`run`], but reports why nothing was sent when every input was
+/// filtered out.
+pub fn run_with_suppression<R: Read>(
+    stdin: R,
+    options: &NotificationOptions,
+) -> Result<Option<SuppressionReason>> {
+    // Read all input from stdin, tolerating invalid UTF-8
+    let buffer = if options.max_input_bytes > 0 {
+        input::read_capped(stdin, options.max_input_bytes)?
+    } else {
+        input::read_stdin_lossy(stdin)?
+    };
+    let diagnostics = &options.diagnostics;
+    diagnostics.log(1, &format!("Read {} bytes of input", buffer.len()));
+
+    // In raw mode stdin is the message itself. Otherwise parse the JSON
+    // input; with --coalesce it may hold several documents
+    let parsed: Vec<NotificationInput> = if options.raw {
+        vec![input::raw_input(&buffer)]
+    } else if options.coalesce {
+        serde_json::Deserializer::from_str(&buffer)
+            .into_iter::<serde_json::Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| input::json_syntax_error(&buffer, &e))?
+            .into_iter()
+            .map(|value| input_from_value(value, options))
+            .collect::<Result<_>>()?
+    } else {
+        vec![input_from_value(input::parse_json(&buffer)?, options)?]
+    };
+    diagnostics.log(
+        1,
+        &format!(
+            "Parsed {} input(s) as {}",
+            parsed.len(),
+            if options.raw { "plain text" } else { "JSON" }
+        ),
+    );
+    for input in &parsed {
+        diagnostics.log(
+            2,
+            &format!(
+                "Input: session '{}', event '{}', {} message bytes",
+                input.session_id,
+                input.hook_event_name.as_deref().unwrap_or("none"),
+                input.message.len()
+            ),
+        );
+    }
+
+    let now = chrono::Local::now().time();
+    let mut inputs = Vec::new();
+    let mut suppressed = None;
+    for input in parsed {
+        match suppression_reason(Some(&input), options, now)? {
+            Some(reason) => {
+                diagnostics.log(1, &format!("Suppressed: {}", reason.as_str()));
+                suppressed = suppressed.or(Some(reason))
             }
+            None => inputs.push(
+                input.normalize_in(options.locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE)),
+            ),
         }
-        Err(e) => {
-            // Log a warning but don't fail the whole notification
-            eprintln!(
-                "Warning: Failed to execute afplay for sound '{}': {}",
-                sound_path, e
-            );
+    }
+
+    // Quiet hours in silent mode still notify, just without a sound
+    let mut options = options.clone();
+    if options
+        .quiet_hours
+        .is_some_and(|quiet| quiet.mode == quiet::QuietMode::Silent && quiet.contains(now))
+    {
+        options.sound_policy = SoundPolicy::Never;
+    }
+
+    // The input has already been read, so the delay never holds up stdin
+    if !inputs.is_empty() {
+        wait_before_send(options.delay, thread::sleep);
+    }
+
+    // Create and send the notification
+    match inputs.as_slice() {
+        [] => return Ok(suppressed),
+        [input] => deliver_or_spool(input, &options)?,
+        [first, ..] => {
+            let locale = options.locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE);
+            let title = options
+                .title
+                .as_deref()
+                .unwrap_or_else(|| i18n::localized("title", locale));
+            let (title, message) = input::coalesce_inputs(&inputs, title);
+            let summary = NotificationInput {
+                session_id: first.session_id.clone(),
+                transcript_path: first.transcript_path.clone(),
+                message,
+                title: Some(title),
+                hook_event_name: None,
+                stop_hook_active: None,
+                timeout_ms: None,
+            };
+            deliver_or_spool(&summary, &options)?
         }
     }
+    Ok(None)
+}
 
-    Ok(())
+/// Sends the notification, spooling it instead of failing when `spool_dir`
+/// is set.
+fn deliver_or_spool(input: &NotificationInput, options: &NotificationOptions) -> Result<()> {
+    let result = send_notification(input, options);
+    match (result, &options.spool_dir) {
+        (Err(e), Some(dir)) => {
+            spool::spool_input(dir, input)?;
+            options.warnings.warn(&format!(
+                "Failed to deliver notification, spooled for `flush`: {}",
+                e
+            ));
+            Ok(())
+        }
+        (result, _) => result,
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Cursor;
+/// Retries the notifications spooled in `dir` with `options`, returning how
+/// many were delivered. Only desktop delivery is retried (see
+/// [`NotificationOptions::replay`]), and failures stay spooled rather than
+/// being spooled again.
+pub fn flush_spooled(dir: &Path, options: &NotificationOptions) -> Result<usize> {
+    let options = options.clone().replay();
+    spool::flush_spool(dir, |input| send_notification(input, &options))
+}
 
-    #[test]
-    fn test_parse_valid_input() {
-        let input_data = r#"{
-            "session_id": "test-session-123",
-            "transcript_path": "/path/to/transcript.md",
-            "message": "Test notification message",
-            "title": "Test Title"
-        }"#;
+/// Parses a `--delay` value in milliseconds.
+pub fn parse_delay(s: &str) -> Result<Duration, String> {
+    s.trim()
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|_| format!("invalid delay '{}', expected milliseconds", s))
+}
 
-        // Test that we can parse the JSON correctly
-        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
-        assert!(input.is_ok());
+/// Waits `delay` with `sleep` before a notification is shown; a zero delay
+/// returns immediately.
+fn wait_before_send<F: FnOnce(Duration)>(delay: Duration, sleep: F) {
+    if !delay.is_zero() {
+        sleep(delay);
+    }
+}
 
-        let input = input.unwrap();
-        assert_eq!(input.session_id, "test-session-123");
-        assert_eq!(input.message, "Test notification message");
-        assert_eq!(input.title, Some("Test Title".to_string()));
+/// Builds a normalized input from one JSON document, renaming any remapped
+/// fields first.
+fn input_from_value(
+    value: serde_json::Value,
+    options: &NotificationOptions,
+) -> Result<NotificationInput> {
+    let value = input::remap_fields(value, &options.field_map);
+    if options.validate_input {
+        #[cfg(feature = "schema")]
+        input::validate_input_against_schema(&value)?;
+        #[cfg(not(feature = "schema"))]
+        options
+            .warnings
+            .warn("Skipped --validate-input: built without the `schema` feature");
     }
+    let mut input = input::input_from_json(value)?;
+    if input.hook_event_name.is_none() {
+        input.hook_event_name = options.hook_event.clone();
+    }
+    Ok(input)
+}
 
-    #[test]
-    fn test_parse_missing_title() {
-        let input_data = r#"{
-            "session_id": "test-session-456",
-            "transcript_path": "/path/to/transcript.md",
-            "message": "Message without title"
-        }"#;
+/// Why a notification was filtered out instead of sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionReason {
+    /// The message was classified below `min_severity`.
+    Severity,
+    /// Quiet hours are active in `suppress` mode.
+    QuietHours,
+    /// The session was already notified with `once_per_session`.
+    OncePerSession,
+    /// Notifications were turned off with the `disable` subcommand.
+    Disabled,
+    /// The session reached `max_per_session` notifications.
+    SessionLimit,
+    /// The event had no message and `skip_empty` is set.
+    EmptyMessage,
+}
 
-        // Test that we can parse the JSON correctly
-        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
-        assert!(input.is_ok());
+impl SuppressionReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SuppressionReason::Severity => "severity",
+            SuppressionReason::QuietHours => "quiet-hours",
+            SuppressionReason::OncePerSession => "once-per-session",
+            SuppressionReason::Disabled => "disabled",
+            SuppressionReason::SessionLimit => "session-limit",
+            SuppressionReason::EmptyMessage => "empty-message",
+        }
+    }
+}
 
-        let input = input.unwrap();
-        assert_eq!(input.session_id, "test-session-456");
-        assert_eq!(input.message, "Message without title");
-        assert_eq!(input.title, None);
+/// Exit code for a run that was suppressed for `reason`, or 0 if the
+/// notification went out. Skipping an empty event, a disabled notifier, and
+/// a session past its cap are normal outcomes rather than filters, so they
+/// always exit 0.
+pub fn suppression_exit_code(reason: Option<SuppressionReason>, exit_on_suppress: i32) -> i32 {
+    match reason {
+        Some(
+            SuppressionReason::Severity
+            | SuppressionReason::QuietHours
+            | SuppressionReason::OncePerSession,
+        ) => exit_on_suppress,
+        Some(
+            SuppressionReason::EmptyMessage
+            | SuppressionReason::Disabled
+            | SuppressionReason::SessionLimit,
+        )
+        | None => 0,
     }
+}
 
-    #[test]
-    fn test_parse_invalid_json() {
-        let invalid_json = "{ invalid json }";
-        let cursor = Cursor::new(invalid_json);
-        let result = main(cursor, Sound::Glass);
+/// Returns `Disabled` when the marker file at `state` has turned
+/// notifications off, unless `force` overrides it.
+fn disabled_reason(state: &Path, force: bool) -> Option<SuppressionReason> {
+    (!force && !state::notifications_enabled(state)).then_some(SuppressionReason::Disabled)
+}
 
-        assert!(result.is_err());
+/// Runs every filter in turn, returning the first one that suppresses the
+/// notification. Without an input only `disable` is checked, so a disabled
+/// notifier can stop before reading its input. The once-per-session and
+/// per-session cap checks go last since they record the session.
+pub fn suppression_reason(
+    input: Option<&NotificationInput>,
+    options: &NotificationOptions,
+    now: chrono::NaiveTime,
+) -> Result<Option<SuppressionReason>> {
+    if let Some(reason) =
+        state::disabled_path().and_then(|state| disabled_reason(&state, options.force))
+    {
+        return Ok(Some(reason));
     }
+    let Some(input) = input else {
+        return Ok(None);
+    };
 
-    #[test]
-    fn test_empty_input() {
-        let empty_input = "";
-        let cursor = Cursor::new(empty_input);
-        let result = main(cursor, Sound::Glass);
+    if options.skip_empty && input.message.trim().is_empty() {
+        return Ok(Some(SuppressionReason::EmptyMessage));
+    }
 
-        assert!(result.is_err());
+    // Severity is judged on the message that would be shown, which for
+    // events without one is the default message
+    let locale = options.locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE);
+    let severity = classify::classify_severity(input.message_in(locale));
+    if !classify::passes_filter(severity, options.min_severity) {
+        return Ok(Some(SuppressionReason::Severity));
     }
 
-    #[test]
-    fn test_sound_from_name() {
-        assert!(matches!(Sound::from_name("Glass"), Sound::Glass));
-        assert!(matches!(Sound::from_name("Submarine"), Sound::Submarine));
-        assert!(matches!(Sound::from_name("CustomSound"), Sound::Custom(_)));
+    if let Some(quiet) = options.quiet_hours {
+        if quiet.mode == quiet::QuietMode::Suppress && quiet.contains(now) {
+            return Ok(Some(SuppressionReason::QuietHours));
+        }
     }
 
-    #[test]
-    fn test_sound_as_str() {
-        assert_eq!(Sound::Glass.as_str(), "Glass");
-        assert_eq!(Sound::Submarine.as_str(), "Submarine");
-        assert_eq!(Sound::Custom("Test".to_string()).as_str(), "Test");
+    if options.once_per_session {
+        if let Some(state) = state::seen_sessions_path() {
+            if !state::mark_and_check_session(&state, &input.session_id)? {
+                return Ok(Some(SuppressionReason::OncePerSession));
+            }
+        }
     }
 
-    #[test]
-    fn test_sound_default() {
-        assert!(matches!(Sound::default(), Sound::Glass));
+    if let Some(max) = options.max_per_session {
+        if let Some(state) = state::session_counts_path() {
+            if !state::increment_and_check(&state, &input.session_id, max)? {
+                return Ok(Some(SuppressionReason::SessionLimit));
+            }
+        }
     }
 
-    #[test]
-    fn test_notification_input_with_special_characters() {
-        let input_data = r#"{
-            "session_id": "test-session-789",
-            "transcript_path": "/path/to/transcript.md",
-            "message": "Message with \"quotes\" and special chars",
-            "title": "Title with \"quotes\""
-        }"#;
+    Ok(None)
+}
 
-        // Test that we can parse the JSON with special characters correctly
-        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
-        assert!(input.is_ok());
+/// Returns the transcript file's creation time, if the platform reports it.
+fn transcript_created_at(transcript_path: &str) -> Option<SystemTime> {
+    std::fs::metadata(transcript_path).ok()?.created().ok()
+}
 
-        let input = input.unwrap();
-        assert_eq!(input.message, "Message with \"quotes\" and special chars");
-        assert_eq!(input.title, Some("Title with \"quotes\"".to_string()));
+fn send_notification(input: &NotificationInput, options: &NotificationOptions) -> Result<()> {
+    let mut notifier_options = options.clone();
+    notifier_options.timeout_ms = effective_timeout(input.timeout_ms, options.timeout_ms);
+    notifier_options.sound = sound_for_event(
+        input.hook_event_name.as_deref(),
+        &options.event_sounds,
+        &options.sound,
+    );
+    if let Some(sound) = options.session_sounds.sound_for(&input.session_id) {
+        notifier_options.sound = Sound::from_name(sound);
+    }
+    let meta = if options.transcript_front_matter {
+        transcript::read_transcript_front_matter(&input.transcript_path)
+    } else {
+        transcript::TranscriptMeta::default()
+    };
+    if let Some(sound) = &meta.sound {
+        notifier_options.sound = Sound::from_name(sound);
+    }
+    if let Some(format) = output::stdout_fallback(
+        options.format,
+        options.backend,
+        options.fallback_stdout,
+        backend::notifications_supported,
+    ) {
+        notifier_options.format = format;
+    }
+    if options.show_elapsed && options.started_at.is_none() {
+        notifier_options.started_at = transcript_created_at(&input.transcript_path);
     }
 
-    #[test]
-    fn test_sound_path_resolution() {
-        // Test system sound path resolution
-        assert_eq!(
-            Sound::Glass.get_afplay_path(),
-            "/System/Library/Sounds/Glass.aiff"
-        );
-        assert_eq!(
-            Sound::Submarine.get_afplay_path(),
-            "/System/Library/Sounds/Submarine.aiff"
-        );
+    let mut notifier =
+        Notifier::new()
+            .options(notifier_options)
+            .thread_id(backend::resolve_group_key(
+                options.group_key.as_deref(),
+                &input.session_id,
+            ));
+    if let Some(title) = meta.title.as_ref().or(input.title.as_ref()) {
+        notifier = notifier.title(title.as_str());
+    }
 
-        // Test custom path pass-through
-        let custom_sound = Sound::Custom("/custom/path/sound.wav".to_string());
-        assert_eq!(custom_sound.get_afplay_path(), "/custom/path/sound.wav");
+    let message = match &options.message_template {
+        Some(template) => format::render_body(template, input, &notifier.resolved_title()),
+        None => input.message.clone(),
+    };
+    // Added after the body transforms so --summary-lines can't cut them
+    let mut suffix = String::new();
+    if options.since_last {
+        if let Some(path) = state::last_notified_path() {
+            match state::since_last(&path, &input.session_id, SystemTime::now()) {
+                Ok(Some(elapsed)) => suffix.push_str(&format!(
+                    " ({} since last)",
+                    format::format_duration(elapsed)
+                )),
+                Ok(None) => {}
+                Err(e) => options.warnings.warn(&format!(
+                    "Failed to update the last notification time: {}",
+                    e
+                )),
+            }
+        }
+    }
+    // No backend can set a dock or taskbar badge, so the count follows the
+    // message text instead
+    if options.count_badge {
+        if let Some(state) = state::badge_path() {
+            match state::bump_badge(&state) {
+                Ok(count) => suffix.push_str(&format!(" ({} unread)", count)),
+                Err(e) => options
+                    .warnings
+                    .warn(&format!("Failed to update the badge count: {}", e)),
+            }
+        }
+    }
+    if let Some(mode) = options.transcript_display {
+        let home = std::env::var("HOME").unwrap_or_default();
+        let path = format::display_path(&input.transcript_path, &home, mode);
+        suffix.push_str(&format!("\n📄 {}", path));
+    }
+    if options.transcript_summary {
+        if let Some(summary) = transcript::summarize_transcript(&input.transcript_path) {
+            suffix.push_str(&format!("\n{}", summary.describe()));
+        }
+    }
 
-        // Test relative path pass-through
-        let relative_sound = Sound::Custom("./sounds/custom.aiff".to_string());
-        assert_eq!(relative_sound.get_afplay_path(), "./sounds/custom.aiff");
+    let notifier = notifier.message(message).suffix(suffix);
+    let prepared = notifier.prepare();
+
+    let id = generate_notification_id(input, SystemTime::now());
+
+    if let Some(url) = &options.webhook {
+        let result = webhook::post_webhook(url, input, &prepared.title, &id);
+        if options.webhook_also {
+            // The desktop notification still goes out, so only warn
+            if let Err(e) = result {
+                options
+                    .warnings
+                    .warn(&format!("Failed to post webhook: {}", e));
+            }
+        } else {
+            result?;
+        }
     }
 
-    #[test]
-    fn test_sound_path_edge_cases() {
-        // Test sound name that happens to contain a slash but isn't meant as a path
-        let edge_case = Sound::Custom("weird/name".to_string());
-        assert_eq!(edge_case.get_afplay_path(), "weird/name");
+    if options.as_reminder {
+        backend::create_reminder(&prepared.body, options.reminder_due)?;
+    } else if options.webhook.is_none() || options.webhook_also {
+        notifier.send()?;
+    }
 
-        // Test empty custom sound (edge case) - gets treated as system sound
-        let empty_custom = Sound::Custom("".to_string());
-        assert_eq!(
-            empty_custom.get_afplay_path(),
-            "/System/Library/Sounds/.aiff"
-        );
+    if options.copy {
+        if let Err(e) = clipboard::copy_to_clipboard(&input.message) {
+            options
+                .warnings
+                .warn(&format!("Failed to copy the message: {}", e));
+        }
+    }
+
+    if options.bell && options.format != output::OutputFormat::Bell {
+        if let Err(e) = output::ring_terminal_bell() {
+            options
+                .warnings
+                .warn(&format!("Failed to ring the terminal bell: {}", e));
+        }
+    }
+
+    if let Some(target) = &options.push {
+        if let Err(e) = push::send_request(&target.request(&prepared.title, &prepared.body)) {
+            options
+                .warnings
+                .warn(&format!("Failed to send push notification: {}", e));
+        }
+    }
+
+    if options.emit_id {
+        println!("{}", id);
+    }
+
+    if let Some(dir) = &options.save_card {
+        #[cfg(feature = "card")]
+        {
+            let link = options
+                .card_qr_base
+                .as_deref()
+                .and_then(|base| card::transcript_link(base, &input.transcript_path));
+            if let Err(e) =
+                card::save_card(dir, &id, &prepared.title, &prepared.body, link.as_deref())
+            {
+                options
+                    .warnings
+                    .warn(&format!("Failed to save the notification card: {}", e));
+            }
+        }
+        #[cfg(not(feature = "card"))]
+        options.warnings.warn(&format!(
+            "Can't save a card to {}: built without the `card` feature",
+            dir.display()
+        ));
+    }
+
+    if let Some(reply_file) = &options.reply_file {
+        if backend::supports_reply(options.backend) {
+            let result =
+                backend::prompt_reply(&prepared.title, &prepared.body).and_then(
+                    |text| match text {
+                        Some(text) => reply::record_reply(reply_file, &input.session_id, &text),
+                        None => Ok(()),
+                    },
+                );
+            if let Err(e) = result {
+                options
+                    .warnings
+                    .warn(&format!("Failed to record reply: {}", e));
+            }
+        } else {
+            options
+                .warnings
+                .warn("Replies aren't supported by this backend or platform");
+        }
+    }
+
+    if options.syslog {
+        let line = format!("{}: {}", prepared.title, prepared.body.replace('\n', " "));
+        if let Err(e) = syslog::log_to_syslog(syslog::SYSLOG_TAG, &line) {
+            options
+                .warnings
+                .warn(&format!("Failed to write to the system log: {}", e));
+        }
+    }
+
+    if let Some(log_file) = &options.log_file {
+        let event = history::LogEvent {
+            id: id.clone(),
+            ..history::LogEvent::new(
+                &input.session_id,
+                &prepared.title,
+                &prepared.body,
+                SystemTime::now(),
+            )
+        };
+        if let Err(e) = history::append_event(log_file, &event) {
+            options
+                .warnings
+                .warn(&format!("Failed to write notification log: {}", e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Latency of the notification sends made by `--bench`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchStats {
+    pub runs: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+}
+
+impl BenchStats {
+    /// Summarizes the measured samples, or `None` if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let runs = samples.len();
+        Some(BenchStats {
+            runs,
+            min: *samples.iter().min()?,
+            max: *samples.iter().max()?,
+            mean: samples.iter().sum::<Duration>() / u32::try_from(runs).ok()?,
+        })
+    }
+}
+
+/// Sends `n` synthetic notifications with `options` and measures each send.
+/// Logging, syslog, and the badge counter are skipped so benchmarks leave no
+/// trace; `silent` also skips the sound.
+pub fn bench_send(options: &NotificationOptions, n: usize, silent: bool) -> Result<BenchStats> {
+    let mut options = options.clone();
+    options.log_file = None;
+    options.syslog = false;
+    options.count_badge = false;
+    if silent {
+        options.sound_policy = SoundPolicy::Never;
+    }
+
+    let input = NotificationInput {
+        session_id: "bench".to_string(),
+        transcript_path: String::new(),
+        message: "Benchmark notification".to_string(),
+        title: Some(DEFAULT_TITLE.to_string()),
+        hook_event_name: None,
+        stop_hook_active: None,
+        timeout_ms: None,
+    };
+
+    bench_with(n, || {
+        let start = Instant::now();
+        send_notification(&input, &options)?;
+        Ok(start.elapsed())
+    })
+}
+
+/// Runs `send` `n` times, each returning how long it took.
+fn bench_with<F: FnMut() -> Result<Duration>>(n: usize, mut send: F) -> Result<BenchStats> {
+    let samples = (0..n).map(|_| send()).collect::<Result<Vec<_>>>()?;
+    BenchStats::from_samples(&samples).context("Benchmark needs at least one run")
+}
+
+/// Derives an id for correlating a notification with later events from its
+/// session id and send time, as 16 hex digits.
+pub fn generate_notification_id(input: &NotificationInput, now: SystemTime) -> String {
+    let nanos = now
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let key = format!("{}\0{}", input.session_id, nanos);
+    format!("{:016x}", stable_hash(key.as_bytes()))
+}
+
+/// Hashes bytes with 64-bit FNV-1a. Unlike `DefaultHasher`, the result is
+/// stable across Rust releases, so it is safe to persist (e.g. in file names).
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Resolves the path handed to `afplay`, downloading remote sounds into the
+/// cache and falling back to the default sound if that fails.
+fn resolve_sound_path(sound: &Sound, sound_dir: &Path, warnings: &WarningSink) -> String {
+    let name = sound.as_str();
+    if !remote::is_remote_sound(name) {
+        return sound.get_afplay_path(sound_dir);
+    }
+
+    let result = match paths::cache_dir() {
+        Some(cache_dir) => remote::resolve_remote_sound(name, &cache_dir.join("sounds")),
+        None => Err(anyhow::anyhow!("no cache directory available")),
+    };
+
+    match result {
+        Ok(path) => path.display().to_string(),
+        Err(e) => {
+            let fallback = Sound::default();
+            warnings.warn(&format!(
+                "Failed to download sound '{}': {}. Falling back to '{}'",
+                name,
+                e,
+                fallback.as_str()
+            ));
+            fallback.get_afplay_path(sound_dir)
+        }
+    }
+}
+
+/// Builds the arguments passed to `afplay` for a resolved sound path.
+fn afplay_args(sound_path: &str, playback: &Playback) -> Vec<String> {
+    let mut args = Vec::new();
+    if (playback.volume - 1.0).abs() > f32::EPSILON {
+        args.push("-v".to_string());
+        args.push(playback.volume.to_string());
+    }
+    args.extend(playback.extra_args.iter().cloned());
+    args.push(sound_path.to_string());
+    args
+}
+
+/// Splits a command line into arguments the way a POSIX shell would:
+/// whitespace separates arguments, single quotes are literal, double quotes
+/// allow `\"` and `\\` escapes, and a backslash elsewhere escapes the next
+/// character. An unterminated quote runs to the end of the string.
+pub fn split_player_args(s: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '\'' => {
+                in_arg = true;
+                current.extend(chars.by_ref().take_while(|c| *c != '\''));
+            }
+            '"' => {
+                in_arg = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(next @ ('"' | '\\')) => current.push(next),
+                            Some(next) => {
+                                current.push('\\');
+                                current.push(next);
+                            }
+                            None => current.push('\\'),
+                        },
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_arg = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Plays the sound with `afplay`, returning a
+/// [`NotificationError::SoundPlayback`] if it could not be executed or
+/// exited unsuccessfully.
+pub fn play_sound(sound: &Sound, playback: &Playback) -> Result<Sound> {
+    let (played, sound_path) = resolve_playable(sound, playback)?;
+    warn_if_unsupported_audio(&sound_path, &playback.warnings);
+    let args = afplay_args(&sound_path, playback);
+
+    play_repeated(repeat_count(playback.repeat), REPEAT_GAP, || {
+        // Execute the player command to play the sound
+        let result = Command::new(&playback.player)
+            .args(&args)
+            .output()
+            .map_err(|e| {
+                NotificationError::sound_playback(
+                    &sound_path,
+                    format!("failed to execute {}: {}", playback.player, e),
+                )
+            })?;
+
+        if !result.status.success() {
+            return Err(NotificationError::sound_playback(
+                &sound_path,
+                format!("{} exit code: {:?}", playback.player, result.status.code()),
+            )
+            .into());
+        }
+
+        Ok(())
+    })?;
+
+    Ok(played)
+}
+
+/// Resolves `sound` to the file handed to the player, switching to
+/// `playback.fallback` when the sound's file doesn't exist. Returns the sound
+/// that will play along with its path.
+fn resolve_playable(sound: &Sound, playback: &Playback) -> Result<(Sound, String)> {
+    let sound_path = resolve_sound_path(sound, &playback.sound_dir, &playback.warnings);
+    if Path::new(&sound_path).exists() {
+        return Ok((sound.clone(), sound_path));
+    }
+
+    let fallback_path =
+        resolve_sound_path(&playback.fallback, &playback.sound_dir, &playback.warnings);
+    if !Path::new(&fallback_path).exists() {
+        return Err(NotificationError::sound_playback(
+            &sound_path,
+            format!("file not found, nor is the fallback {}", fallback_path),
+        )
+        .into());
+    }
+
+    playback.warnings.warn(&format!(
+        "Sound file '{}' not found, playing '{}' instead",
+        sound_path,
+        playback.fallback.as_str()
+    ));
+    Ok((playback.fallback.clone(), fallback_path))
+}
+
+/// Starts afplay without waiting for it to exit, so the hook can return
+/// before the sound finishes. The child's stdio is detached so the caller
+/// isn't kept waiting on inherited pipes. Repeats are not supported here
+/// since there is nothing left to pace them.
+pub(crate) fn spawn_sound(sound: &Sound, playback: &Playback) -> Result<()> {
+    let (_, sound_path) = resolve_playable(sound, playback)?;
+    warn_if_unsupported_audio(&sound_path, &playback.warnings);
+    let args = afplay_args(&sound_path, playback);
+
+    Command::new(&playback.player)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            NotificationError::sound_playback(
+                &sound_path,
+                format!("failed to execute {}: {}", playback.player, e),
+            )
+        })?;
+
+    Ok(())
+}
+
+/// Decides what a sound failure means for the overall result. By default
+/// failures only log a warning; in strict mode they are propagated.
+pub(crate) fn handle_sound_result(
+    result: Result<()>,
+    strict: bool,
+    warnings: &WarningSink,
+) -> Result<()> {
+    match result {
+        Err(e) if strict => Err(e),
+        Err(e) => {
+            // Log a warning but don't fail the whole notification
+            warnings.warn(&e.to_string());
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Combines the notification and sound results into the overall result.
+/// `has_sound` is false when no sound was played, in which case only the
+/// notification counts toward `options.on_failure`.
+pub(crate) fn handle_delivery_results(
+    notification_result: Result<()>,
+    sound_result: Result<()>,
+    has_sound: bool,
+    options: &NotificationOptions,
+) -> Result<()> {
+    let Some(policy) = options.on_failure else {
+        // The notification result takes precedence over the sound result
+        notification_result?;
+        return handle_sound_result(sound_result, options.strict_sound, &options.warnings);
+    };
+
+    let notif_ok = notification_result.is_ok();
+    let sound_ok = if has_sound {
+        sound_result.is_ok()
+    } else {
+        notif_ok
+    };
+    let mut errors = [notification_result.err(), sound_result.err()]
+        .into_iter()
+        .flatten();
+    let first = match compute_exit(notif_ok, sound_ok, policy) {
+        0 => None,
+        _ => errors.next(),
+    };
+    for e in errors {
+        options.warnings.warn(&e.to_string());
+    }
+    first.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_valid_input() {
+        let input_data = r#"{
+            "session_id": "test-session-123",
+            "transcript_path": "/path/to/transcript.md",
+            "message": "Test notification message",
+            "title": "Test Title"
+        }"#;
+
+        // Test that we can parse the JSON correctly
+        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
+        assert!(input.is_ok());
+
+        let input = input.unwrap();
+        assert_eq!(input.session_id, "test-session-123");
+        assert_eq!(input.message, "Test notification message");
+        assert_eq!(input.title, Some("Test Title".to_string()));
+    }
+
+    #[test]
+    fn test_parse_missing_title() {
+        let input_data = r#"{
+            "session_id": "test-session-456",
+            "transcript_path": "/path/to/transcript.md",
+            "message": "Message without title"
+        }"#;
+
+        // Test that we can parse the JSON correctly
+        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
+        assert!(input.is_ok());
+
+        let input = input.unwrap();
+        assert_eq!(input.session_id, "test-session-456");
+        assert_eq!(input.message, "Message without title");
+        assert_eq!(input.title, None);
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        let invalid_json = "{ invalid json }";
+        let cursor = Cursor::new(invalid_json);
+        let result = main(cursor, Sound::Glass);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let empty_input = "";
+        let cursor = Cursor::new(empty_input);
+        let result = main(cursor, Sound::Glass);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sound_from_name() {
+        assert!(matches!(Sound::from_name("Glass"), Sound::Glass));
+        assert!(matches!(Sound::from_name("Submarine"), Sound::Submarine));
+        assert!(matches!(Sound::from_name("CustomSound"), Sound::Custom(_)));
+        assert_eq!(Sound::from_name("Default"), Sound::SystemDefault);
+        assert!(matches!(Sound::from_name("default"), Sound::Custom(_)));
+    }
+
+    #[test]
+    fn test_sound_as_str() {
+        assert_eq!(Sound::Glass.as_str(), "Glass");
+        assert_eq!(Sound::Submarine.as_str(), "Submarine");
+        assert_eq!(Sound::Custom("Test".to_string()).as_str(), "Test");
+        assert_eq!(Sound::SystemDefault.as_str(), "Default");
+    }
+
+    #[test]
+    fn test_sound_default() {
+        assert!(matches!(Sound::default(), Sound::Glass));
+    }
+
+    #[test]
+    fn test_notification_input_with_special_characters() {
+        let input_data = r#"{
+            "session_id": "test-session-789",
+            "transcript_path": "/path/to/transcript.md",
+            "message": "Message with \"quotes\" and special chars",
+            "title": "Title with \"quotes\""
+        }"#;
+
+        // Test that we can parse the JSON with special characters correctly
+        let input: Result<NotificationInput, _> = serde_json::from_str(input_data);
+        assert!(input.is_ok());
+
+        let input = input.unwrap();
+        assert_eq!(input.message, "Message with \"quotes\" and special chars");
+        assert_eq!(input.title, Some("Title with \"quotes\"".to_string()));
+    }
+
+    #[test]
+    fn test_sound_path_resolution() {
+        // Test system sound path resolution
+        assert_eq!(
+            Sound::Glass.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "/System/Library/Sounds/Glass.aiff"
+        );
+        assert_eq!(
+            Sound::Submarine.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "/System/Library/Sounds/Submarine.aiff"
+        );
+
+        // Test custom path pass-through
+        let custom_sound = Sound::Custom("/custom/path/sound.wav".to_string());
+        assert_eq!(
+            custom_sound.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "/custom/path/sound.wav"
+        );
+
+        // Test relative path pass-through
+        let relative_sound = Sound::Custom("./sounds/custom.aiff".to_string());
+        assert_eq!(
+            relative_sound.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "./sounds/custom.aiff"
+        );
+    }
+
+    #[test]
+    fn test_sound_path_custom_base_dir() {
+        let base = Path::new("/opt/sounds");
+        assert_eq!(Sound::Glass.get_afplay_path(base), "/opt/sounds/Glass.aiff");
+        assert_eq!(
+            Sound::Custom("Chime".to_string()).get_afplay_path(base),
+            "/opt/sounds/Chime.aiff"
+        );
+
+        // Names with a slash are paths and ignore the base directory
+        let custom = Sound::Custom("./sounds/custom.wav".to_string());
+        assert_eq!(custom.get_afplay_path(base), "./sounds/custom.wav");
+    }
+
+    #[test]
+    fn test_sound_path_edge_cases() {
+        // Test sound name that happens to contain a slash but isn't meant as a path
+        let edge_case = Sound::Custom("weird/name".to_string());
+        assert_eq!(
+            edge_case.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "weird/name"
+        );
+
+        // Test empty custom sound (edge case) - gets treated as system sound
+        let empty_custom = Sound::Custom("".to_string());
+        assert_eq!(
+            empty_custom.get_afplay_path(Path::new(SYSTEM_SOUNDS_DIR)),
+            "/System/Library/Sounds/.aiff"
+        );
+    }
+
+    #[test]
+    fn test_sound_policy_from_name() {
+        assert_eq!(SoundPolicy::from_name("always"), Some(SoundPolicy::Always));
+        assert_eq!(SoundPolicy::from_name("error"), Some(SoundPolicy::Error));
+        assert_eq!(SoundPolicy::from_name("never"), Some(SoundPolicy::Never));
+        assert_eq!(SoundPolicy::from_name("sometimes"), None);
+        assert_eq!(SoundPolicy::default(), SoundPolicy::Always);
+    }
+
+    #[test]
+    fn test_should_play_sound_always() {
+        assert!(should_play_sound("Build failed", SoundPolicy::Always));
+        assert!(should_play_sound("All tasks complete", SoundPolicy::Always));
+    }
+
+    #[test]
+    fn test_should_play_sound_error() {
+        assert!(should_play_sound("Build failed", SoundPolicy::Error));
+        assert!(!should_play_sound("All tasks complete", SoundPolicy::Error));
+    }
+
+    #[test]
+    fn test_should_play_sound_never() {
+        assert!(!should_play_sound("Build failed", SoundPolicy::Never));
+        assert!(!should_play_sound("All tasks complete", SoundPolicy::Never));
+    }
+
+    #[test]
+    fn test_default_message_for_known_events() {
+        assert_eq!(default_message_for_event("Stop"), "Claude finished");
+        assert_eq!(
+            default_message_for_event("SubagentStop"),
+            "Claude subagent finished"
+        );
+        assert_eq!(
+            default_message_for_event("Notification"),
+            "Claude needs input"
+        );
+    }
+
+    #[test]
+    fn test_default_message_for_unknown_event() {
+        assert_eq!(
+            default_message_for_event("PreToolUse"),
+            "Claude Code notification"
+        );
+        assert_eq!(default_message_for_event(""), "Claude Code notification");
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        assert_eq!(stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(stable_hash(b"session-1"), stable_hash(b"session-2"));
+    }
+
+    #[test]
+    fn test_generate_notification_id() {
+        let input =
+            input::parse_input(r#"{"session_id": "abc", "transcript_path": "/t"}"#).unwrap();
+        let now = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let id = generate_notification_id(&input, now);
+        assert_eq!(id.len(), 16);
+        assert_eq!(id, generate_notification_id(&input, now));
+        assert_ne!(
+            id,
+            generate_notification_id(&input, now + Duration::from_millis(1))
+        );
+    }
+
+    #[test]
+    fn test_suppression_exit_code() {
+        for reason in [
+            SuppressionReason::Severity,
+            SuppressionReason::QuietHours,
+            SuppressionReason::OncePerSession,
+        ] {
+            assert_eq!(suppression_exit_code(Some(reason), 3), 3);
+            assert_eq!(suppression_exit_code(Some(reason), 0), 0);
+        }
+        for reason in [
+            SuppressionReason::EmptyMessage,
+            SuppressionReason::Disabled,
+            SuppressionReason::SessionLimit,
+        ] {
+            assert_eq!(suppression_exit_code(Some(reason), 3), 0);
+        }
+        assert_eq!(suppression_exit_code(None, 3), 0);
+    }
+
+    #[test]
+    fn test_disabled_reason() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("disabled");
+
+        assert_eq!(disabled_reason(&state, false), None);
+
+        state::set_enabled(&state, false).unwrap();
+        assert_eq!(
+            disabled_reason(&state, false),
+            Some(SuppressionReason::Disabled)
+        );
+        assert_eq!(disabled_reason(&state, true), None);
+    }
+
+    #[test]
+    fn test_suppression_reason() {
+        let input = input::parse_input(
+            r#"{"session_id": "abc", "transcript_path": "/t", "message": "All done"}"#,
+        )
+        .unwrap();
+        let noon = chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let quiet = quiet::QuietHours {
+            start: chrono::NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            end: chrono::NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+            mode: quiet::QuietMode::Suppress,
+        };
+
+        let options = NotificationOptions::default();
+        assert_eq!(
+            suppression_reason(Some(&input), &options, noon).unwrap(),
+            None
+        );
+        assert_eq!(suppression_reason(None, &options, noon).unwrap(), None);
+
+        let empty = NotificationInput {
+            message: " ".to_string(),
+            ..input::parse_input(r#"{"session_id": "abc", "transcript_path": "/t"}"#).unwrap()
+        };
+        assert_eq!(
+            suppression_reason(Some(&empty), &options, noon).unwrap(),
+            None
+        );
+        let options = NotificationOptions {
+            skip_empty: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            suppression_reason(Some(&empty), &options, noon).unwrap(),
+            Some(SuppressionReason::EmptyMessage)
+        );
+
+        let options = NotificationOptions {
+            min_severity: classify::Severity::Error,
+            ..Default::default()
+        };
+        assert_eq!(
+            suppression_reason(Some(&input), &options, noon).unwrap(),
+            Some(SuppressionReason::Severity)
+        );
+
+        let options = NotificationOptions {
+            quiet_hours: Some(quiet),
+            ..Default::default()
+        };
+        assert_eq!(
+            suppression_reason(Some(&input), &options, noon).unwrap(),
+            Some(SuppressionReason::QuietHours)
+        );
+
+        let options = NotificationOptions {
+            quiet_hours: Some(quiet::QuietHours {
+                mode: quiet::QuietMode::Silent,
+                ..quiet
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            suppression_reason(Some(&input), &options, noon).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_play_sound_missing_file_fails() {
+        let missing = Sound::Custom("/nonexistent/file.wav".to_string());
+        let err = play_sound(&missing, &Playback::default()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<NotificationError>(),
+            Some(NotificationError::SoundPlayback { sound, .. }) if sound == "/nonexistent/file.wav"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_play_sound_with_stub_player() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let stub = temp_dir.path().join("afplay-stub");
+        std::fs::write(&stub, "#!/bin/sh\nexit 0\n").unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(temp_dir.path().join("Glass.aiff"), "").unwrap();
+
+        let playback = Playback {
+            player: stub.to_string_lossy().into_owned(),
+            sound_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        assert!(play_sound(&Sound::Glass, &playback).is_ok());
+    }
+
+    fn stub_sounds(names: &[&str]) -> (tempfile::TempDir, Playback) {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        for name in names {
+            std::fs::write(temp_dir.path().join(format!("{}.aiff", name)), "").unwrap();
+        }
+        let playback = Playback {
+            player: "true".to_string(),
+            sound_dir: temp_dir.path().to_path_buf(),
+            fallback: Sound::Ping,
+            ..Default::default()
+        };
+        (temp_dir, playback)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_play_sound_primary_present() {
+        let (_dir, playback) = stub_sounds(&["Hero", "Ping"]);
+        assert_eq!(play_sound(&Sound::Hero, &playback).unwrap(), Sound::Hero);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_play_sound_missing_uses_fallback() {
+        let (_dir, playback) = stub_sounds(&["Ping"]);
+        assert_eq!(play_sound(&Sound::Hero, &playback).unwrap(), Sound::Ping);
+    }
+
+    #[test]
+    fn test_play_sound_primary_and_fallback_missing() {
+        let (dir, playback) = stub_sounds(&[]);
+        let err = play_sound(&Sound::Hero, &playback).unwrap_err();
+        let expected = dir.path().join("Hero.aiff").display().to_string();
+        assert!(matches!(
+            err.downcast_ref::<NotificationError>(),
+            Some(NotificationError::SoundPlayback { sound, .. }) if *sound == expected
+        ));
+    }
+
+    #[test]
+    fn test_missing_sound_strict_mode_propagates() {
+        let missing = Sound::Custom("/nonexistent/file.wav".to_string());
+        let result = handle_sound_result(
+            play_sound(&missing, &Playback::default()).map(|_| ()),
+            true,
+            &WarningSink::default(),
+        );
+
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("/nonexistent/file.wav"));
+    }
+
+    #[test]
+    fn test_missing_sound_lenient_mode_warns() {
+        let missing = Sound::Custom("/nonexistent/file.wav".to_string());
+        assert!(handle_sound_result(
+            play_sound(&missing, &Playback::default()).map(|_| ()),
+            false,
+            &WarningSink::default(),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_compute_exit_fail_any() {
+        assert_eq!(compute_exit(true, true, FailurePolicy::FailAny), 0);
+        assert_eq!(compute_exit(true, false, FailurePolicy::FailAny), 1);
+        assert_eq!(compute_exit(false, true, FailurePolicy::FailAny), 1);
+        assert_eq!(compute_exit(false, false, FailurePolicy::FailAny), 1);
+    }
+
+    #[test]
+    fn test_compute_exit_fail_all() {
+        assert_eq!(compute_exit(true, true, FailurePolicy::FailAll), 0);
+        assert_eq!(compute_exit(true, false, FailurePolicy::FailAll), 0);
+        assert_eq!(compute_exit(false, true, FailurePolicy::FailAll), 0);
+        assert_eq!(compute_exit(false, false, FailurePolicy::FailAll), 1);
+    }
+
+    #[test]
+    fn test_compute_exit_never() {
+        assert_eq!(compute_exit(true, true, FailurePolicy::Never), 0);
+        assert_eq!(compute_exit(true, false, FailurePolicy::Never), 0);
+        assert_eq!(compute_exit(false, true, FailurePolicy::Never), 0);
+        assert_eq!(compute_exit(false, false, FailurePolicy::Never), 0);
+    }
+
+    #[test]
+    fn test_failure_policy_from_name() {
+        assert_eq!(
+            FailurePolicy::from_name("fail-any"),
+            Some(FailurePolicy::FailAny)
+        );
+        assert_eq!(
+            FailurePolicy::from_name("fail-all"),
+            Some(FailurePolicy::FailAll)
+        );
+        assert_eq!(
+            FailurePolicy::from_name("never"),
+            Some(FailurePolicy::Never)
+        );
+        assert_eq!(FailurePolicy::from_name("always"), None);
+    }
+
+    #[test]
+    fn test_dry_run_turns_off_side_effects() {
+        let options = NotificationOptions {
+            title: Some("Build".to_string()),
+            sound_policy: SoundPolicy::Always,
+            format: output::OutputFormat::Json,
+            sink: Some(Arc::new(RecordingSink::new())),
+            bell: true,
+            webhook: Some("https://example.com/hook".to_string()),
+            webhook_also: true,
+            syslog: true,
+            spool_dir: Some(PathBuf::from("/tmp/spool")),
+            as_reminder: true,
+            reply_file: Some(PathBuf::from("/tmp/reply")),
+            copy: true,
+            save_card: Some(PathBuf::from("/tmp/cards")),
+            log_file: Some(PathBuf::from("/tmp/history.jsonl")),
+            count_badge: true,
+            once_per_session: true,
+            max_per_session: Some(1),
+            since_last: true,
+            delay: Duration::from_secs(5),
+            ..Default::default()
+        }
+        .dry_run();
+
+        assert_eq!(options.format, output::OutputFormat::Plain);
+        assert!(options.sink.is_none());
+        assert!(!options.bell);
+        assert!(matches!(options.sound_policy, SoundPolicy::Never));
+        assert_eq!(options.webhook, None);
+        assert!(options.push.is_none());
+        assert!(!options.syslog);
+        assert_eq!(options.spool_dir, None);
+        assert!(!options.as_reminder);
+        assert_eq!(options.reply_file, None);
+        assert!(!options.copy);
+        assert_eq!(options.save_card, None);
+        assert_eq!(options.log_file, None);
+        assert!(!options.count_badge);
+        assert!(!options.once_per_session);
+        assert_eq!(options.max_per_session, None);
+        assert!(!options.since_last);
+        assert_eq!(options.delay, Duration::ZERO);
+        // What is shown is unchanged
+        assert_eq!(options.title.as_deref(), Some("Build"));
+    }
+
+    #[test]
+    fn test_flush_only_replays_desktop_delivery() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let input = NotificationInput {
+            session_id: "abc123".to_string(),
+            transcript_path: "/tmp/transcript.jsonl".to_string(),
+            message: "Build finished".to_string(),
+            title: None,
+            hook_event_name: None,
+            stop_hook_active: None,
+            timeout_ms: None,
+        };
+        spool::spool_input(temp_dir.path(), &input).unwrap();
+        let sink = Arc::new(RecordingSink::new());
+        let options = NotificationOptions {
+            sink: Some(sink.clone()),
+            sound_policy: SoundPolicy::Never,
+            count_badge: true,
+            since_last: true,
+            webhook: Some("http://127.0.0.1:9/hook".to_string()),
+            webhook_also: true,
+            log_file: Some(temp_dir.path().join("history.jsonl")),
+            ..Default::default()
+        };
+
+        assert_eq!(flush_spooled(temp_dir.path(), &options).unwrap(), 1);
+
+        // No "(N unread)" means the badge counter wasn't bumped
+        let shown = sink.shown();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].body, "Build finished");
+        assert!(!temp_dir.path().join("history.jsonl").exists());
+    }
+
+    #[test]
+    fn test_handle_delivery_results_with_policy() {
+        let (warnings, collected) = collecting_sink();
+        let options = |policy| NotificationOptions {
+            on_failure: Some(policy),
+            warnings: warnings.clone(),
+            ..Default::default()
+        };
+        let failed = |what: &str| Err(anyhow::anyhow!("{} failed", what));
+
+        // The sound still played, so fail-all lets the notification failure go
+        assert!(handle_delivery_results(
+            failed("notification"),
+            Ok(()),
+            true,
+            &options(FailurePolicy::FailAll)
+        )
+        .is_ok());
+        assert_eq!(*collected.lock().unwrap(), ["notification failed"]);
+
+        // Without a sound, the notification alone decides
+        let result = handle_delivery_results(
+            failed("notification"),
+            Ok(()),
+            false,
+            &options(FailurePolicy::FailAll),
+        );
+        assert_eq!(result.unwrap_err().to_string(), "notification failed");
+
+        let result = handle_delivery_results(
+            Ok(()),
+            failed("sound"),
+            true,
+            &options(FailurePolicy::FailAny),
+        );
+        assert_eq!(result.unwrap_err().to_string(), "sound failed");
+
+        assert!(handle_delivery_results(
+            failed("notification"),
+            failed("sound"),
+            true,
+            &options(FailurePolicy::Never)
+        )
+        .is_ok());
+    }
+
+    fn collecting_sink() -> (WarningSink, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let collected = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = {
+            let collected = std::sync::Arc::clone(&collected);
+            WarningSink::callback(move |msg| collected.lock().unwrap().push(msg.to_string()))
+        };
+        (sink, collected)
+    }
+
+    #[test]
+    fn test_sound_failure_warning_goes_to_sink() {
+        let (sink, collected) = collecting_sink();
+        let missing = Sound::Custom("/nonexistent/file.wav".to_string());
+        let playback = Playback {
+            warnings: sink.clone(),
+            ..Default::default()
+        };
+
+        let result = handle_sound_result(play_sound(&missing, &playback).map(|_| ()), false, &sink);
+
+        assert!(result.is_ok());
+        let collected = collected.lock().unwrap();
+        assert_eq!(collected.len(), 1);
+        assert!(collected[0].contains("/nonexistent/file.wav"));
+        assert!(!collected[0].starts_with("Warning:"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fallback_warning_goes_to_sink() {
+        let (sink, collected) = collecting_sink();
+        let (_dir, playback) = stub_sounds(&["Ping"]);
+        let playback = Playback {
+            warnings: sink,
+            ..playback
+        };
+
+        assert_eq!(play_sound(&Sound::Hero, &playback).unwrap(), Sound::Ping);
+        assert!(collected.lock().unwrap()[0].contains("playing 'Ping' instead"));
+    }
+
+    #[test]
+    fn test_failed_delivery_is_spooled() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let spool = temp_dir.path().join("spool");
+        let (sink, warnings) = collecting_sink();
+        // Nothing listens on port 1, so the webhook fails to deliver
+        let options = NotificationOptions {
+            webhook: Some("http://127.0.0.1:1/".to_string()),
+            spool_dir: Some(spool.clone()),
+            warnings: sink,
+            ..Default::default()
+        };
+
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "message": "hi"}"#;
+        run(Cursor::new(input), &options).unwrap();
+
+        assert_eq!(std::fs::read_dir(&spool).unwrap().count(), 1);
+        assert!(warnings.lock().unwrap()[0].contains("spooled"));
+        assert!(run(
+            Cursor::new(input),
+            &NotificationOptions {
+                spool_dir: None,
+                ..options
+            }
+        )
+        .is_err());
+    }
+
+    fn plain_options(notify_on_empty: bool) -> NotificationOptions {
+        NotificationOptions {
+            skip_empty: !notify_on_empty,
+            format: output::OutputFormat::Plain,
+            sound_policy: SoundPolicy::Never,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_verbose_diagnostics() {
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "message": "Done"}"#;
+        let run_at = |level| {
+            let (sink, collected) = collecting_sink();
+            let options = NotificationOptions {
+                diagnostics: Diagnostics::new(level, sink),
+                ..plain_options(true)
+            };
+            run_with_suppression(Cursor::new(input), &options).unwrap();
+            let lines = collected.lock().unwrap().clone();
+            lines
+        };
+
+        assert!(run_at(0).is_empty());
+        let lines = run_at(1);
+        assert!(lines
+            .iter()
+            .any(|line| line.starts_with("Resolved sound: ")));
+        assert!(!lines.iter().any(|line| line.starts_with("Input: ")));
+        assert!(run_at(2).iter().any(|line| line.starts_with("Input: ")));
+    }
+
+    #[test]
+    fn test_notify_on_empty_false_skips_empty() {
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "message": "  "}"#;
+
+        assert_eq!(
+            run_with_suppression(Cursor::new(input), &plain_options(false)).unwrap(),
+            Some(SuppressionReason::EmptyMessage)
+        );
+    }
+
+    #[test]
+    fn test_notify_on_empty_false_shows_message() {
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "message": "Done"}"#;
+
+        assert_eq!(
+            run_with_suppression(Cursor::new(input), &plain_options(false)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_notify_on_empty_true_shows_default() {
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "hook_event_name": "Stop"}"#;
+
+        assert_eq!(
+            run_with_suppression(Cursor::new(input), &plain_options(true)).unwrap(),
+            None
+        );
+        let value = serde_json::from_str(input).unwrap();
+        let parsed = input_from_value(value, &plain_options(true)).unwrap();
+        assert!(!parsed.normalize().message.is_empty());
+    }
+
+    #[test]
+    fn test_run_raw_plain_text() {
+        let options = NotificationOptions {
+            raw: true,
+            ..plain_options(true)
+        };
+
+        let text = "tests passed: 42\n{not json\n";
+        assert_eq!(
+            run_with_suppression(Cursor::new(text), &options).unwrap(),
+            None
+        );
+        assert!(run(Cursor::new(text), &plain_options(true)).is_err());
+    }
+
+    #[test]
+    fn test_run_raw_empty_input() {
+        let options = NotificationOptions {
+            raw: true,
+            ..plain_options(false)
+        };
+
+        assert_eq!(
+            run_with_suppression(Cursor::new("\n"), &options).unwrap(),
+            Some(SuppressionReason::EmptyMessage)
+        );
+    }
+
+    #[test]
+    fn test_run_with_remapped_fields() {
+        let options = NotificationOptions {
+            field_map: FieldMap {
+                message: Some("text".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The default field name is not accepted once remapped
+        let input = r#"{"session_id": "s", "transcript_path": "/t.md", "message": "hi"}"#;
+        assert!(run(Cursor::new(input), &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_stop_payload() {
+        let input_data = r#"{
+            "session_id": "abc123",
+            "transcript_path": "/Users/dev/.claude/projects/demo/abc123.jsonl",
+            "hook_event_name": "Stop",
+            "stop_hook_active": false
+        }"#;
+
+        let input: NotificationInput = serde_json::from_str(input_data).unwrap();
+        assert_eq!(input.message, "");
+        assert_eq!(input.stop_hook_active, Some(false));
+
+        let input = input.normalize();
+        assert_eq!(input.message, "Claude finished");
+    }
+
+    #[test]
+    fn test_normalize_blank_messages() {
+        for message in ["", "   ", "\n", " \t\n "] {
+            let input = NotificationInput {
+                session_id: "abc123".to_string(),
+                transcript_path: "/t.jsonl".to_string(),
+                message: message.to_string(),
+                title: None,
+                hook_event_name: None,
+                stop_hook_active: None,
+                timeout_ms: None,
+            };
+            assert_eq!(
+                input.normalize().message,
+                "Claude Code notification",
+                "{:?}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_blank_message_uses_event_default() {
+        let input = input::parse_input(
+            r#"{"session_id": "s", "transcript_path": "/t", "message": "  ", "hook_event_name": "Stop"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(input.normalize().message, "Claude finished");
+    }
+
+    #[test]
+    fn test_normalize_keeps_existing_message() {
+        let input_data = r#"{
+            "session_id": "abc123",
+            "transcript_path": "/path/to/transcript.md",
+            "hook_event_name": "Notification",
+            "message": "Claude needs your permission to use Bash"
+        }"#;
+
+        let input: NotificationInput = serde_json::from_str(input_data).unwrap();
+        assert_eq!(
+            input.normalize().message,
+            "Claude needs your permission to use Bash"
+        );
+    }
+
+    #[test]
+    fn test_effective_volume_per_sound_only() {
+        let table = VolumeTable::from([("Basso".to_string(), 0.5)]);
+        assert_eq!(effective_volume(&Sound::Basso, None, &table), 0.5);
+        assert_eq!(effective_volume(&Sound::Glass, None, &table), 1.0);
+    }
+
+    #[test]
+    fn test_effective_volume_cli_only() {
+        let table = VolumeTable::new();
+        assert_eq!(effective_volume(&Sound::Glass, Some(0.8), &table), 0.8);
+    }
+
+    #[test]
+    fn test_effective_volume_combined() {
+        let table = VolumeTable::from([("Hero".to_string(), 0.5)]);
+        assert_eq!(effective_volume(&Sound::Hero, Some(1.5), &table), 0.75);
+    }
+
+    #[test]
+    fn test_split_player_args() {
+        assert_eq!(
+            split_player_args("-r 1.5 -t 2"),
+            vec!["-r", "1.5", "-t", "2"]
+        );
+        assert_eq!(split_player_args("  -q   1 "), vec!["-q", "1"]);
+        assert_eq!(
+            split_player_args(r#"--name "two words" 'it''s' a\ b"#),
+            vec!["--name", "two words", "its", "a b"]
+        );
+        assert_eq!(
+            split_player_args(r#""say \"hi\"" "" ''"#),
+            vec!["say \"hi\"", "", ""]
+        );
+        assert!(split_player_args("").is_empty());
+        assert!(split_player_args("   ").is_empty());
+    }
+
+    #[test]
+    fn test_afplay_args_with_extra_args() {
+        let playback = Playback {
+            volume: 0.5,
+            extra_args: split_player_args("-r 2"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            afplay_args("/tmp/a.wav", &playback),
+            vec!["-v", "0.5", "-r", "2", "/tmp/a.wav"]
+        );
+    }
+
+    #[test]
+    fn test_read_sound_name() {
+        let temp_dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("sound");
+
+        std::fs::write(&path, "Hero").unwrap();
+        assert_eq!(read_sound_name(&path).unwrap(), "Hero");
+
+        std::fs::write(&path, "  /tmp/My Sound.wav\n").unwrap();
+        assert_eq!(read_sound_name(&path).unwrap(), "/tmp/My Sound.wav");
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(read_sound_name(&path).is_err());
+        std::fs::write(&path, "Hero\nPing\n").unwrap();
+        assert!(read_sound_name(&path).is_err());
+    }
+
+    #[test]
+    fn test_read_sound_name_missing_path() {
+        let missing = Path::new("/nonexistent/sound-name");
+
+        assert!(read_sound_name(missing).is_err());
+        let sound = read_sound_name(missing).unwrap_or_else(|_| "Glass".to_string());
+        assert_eq!(sound, "Glass");
+    }
+
+    #[test]
+    fn test_timeout_ms_from_input() {
+        let with = input::parse_input(
+            r#"{"session_id": "s", "transcript_path": "/t", "timeout_ms": 15000}"#,
+        )
+        .unwrap();
+        let without =
+            input::parse_input(r#"{"session_id": "s", "transcript_path": "/t"}"#).unwrap();
+
+        assert_eq!(with.timeout_ms, Some(15000));
+        assert_eq!(without.timeout_ms, None);
+        assert!(input::parse_input(
+            r#"{"session_id": "s", "transcript_path": "/t", "timeout_ms": "soon"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_bench_stats_from_fake_timings() {
+        let mut timings = [30, 10, 20].into_iter().map(Duration::from_millis);
+        let stats = bench_with(3, || Ok(timings.next().unwrap())).unwrap();
+
+        assert_eq!(
+            stats,
+            BenchStats {
+                runs: 3,
+                min: Duration::from_millis(10),
+                max: Duration::from_millis(30),
+                mean: Duration::from_millis(20),
+            }
+        );
+    }
+
+    #[test]
+    fn test_bench_with_no_runs() {
+        assert!(bench_with(0, || Ok(Duration::ZERO)).is_err());
+        assert_eq!(BenchStats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_bench_stops_on_send_error() {
+        let mut calls = 0;
+        let result = bench_with(5, || {
+            calls += 1;
+            anyhow::bail!("no server")
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_parse_delay() {
+        assert_eq!(parse_delay("250"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_delay("0"), Ok(Duration::ZERO));
+        assert!(parse_delay("1s").is_err());
+        assert!(parse_delay("-5").is_err());
+    }
+
+    #[test]
+    fn test_wait_before_send() {
+        assert_eq!(NotificationOptions::default().delay, Duration::ZERO);
+
+        let mut slept = None;
+        wait_before_send(Duration::ZERO, |d| slept = Some(d));
+        assert_eq!(slept, None);
+
+        wait_before_send(Duration::from_millis(300), |d| slept = Some(d));
+        assert_eq!(slept, Some(Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn test_effective_timeout_precedence() {
+        assert_eq!(effective_timeout(Some(15000), Some(5000)), Some(15000));
+        assert_eq!(effective_timeout(None, Some(5000)), Some(5000));
+        assert_eq!(effective_timeout(None, None), None);
+    }
+
+    #[test]
+    fn test_sound_for_event() {
+        let table = EventSounds::from([
+            ("Stop".to_string(), "Hero".to_string()),
+            ("Notification".to_string(), "/tmp/ping.wav".to_string()),
+        ]);
+
+        assert_eq!(
+            sound_for_event(Some("Stop"), &table, &Sound::Glass).as_str(),
+            "Hero"
+        );
+        assert_eq!(
+            sound_for_event(Some("Notification"), &table, &Sound::Glass).as_str(),
+            "/tmp/ping.wav"
+        );
+        assert_eq!(
+            sound_for_event(Some("SubagentStop"), &table, &Sound::Glass).as_str(),
+            "Glass"
+        );
+        assert_eq!(
+            sound_for_event(None, &table, &Sound::Submarine).as_str(),
+            "Submarine"
+        );
+    }
+
+    #[test]
+    fn test_afplay_args() {
+        assert_eq!(
+            afplay_args("/tmp/a.wav", &Playback::default()),
+            vec!["/tmp/a.wav"]
+        );
+        assert_eq!(
+            afplay_args(
+                "/tmp/a.wav",
+                &Playback {
+                    volume: 0.5,
+                    ..Default::default()
+                }
+            ),
+            vec!["-v", "0.5", "/tmp/a.wav"]
+        );
+    }
+
+    fn count_plays(requested: u32) -> u32 {
+        let mut plays = 0;
+        play_repeated(repeat_count(requested), Duration::ZERO, || {
+            plays += 1;
+            Ok(())
+        })
+        .unwrap();
+        plays
+    }
+
+    #[test]
+    fn test_repeat_single() {
+        assert_eq!(count_plays(1), 1);
+        assert_eq!(count_plays(0), 1);
+    }
+
+    #[test]
+    fn test_repeat_multiple() {
+        assert_eq!(count_plays(3), 3);
+    }
+
+    #[test]
+    fn test_repeat_over_cap() {
+        assert_eq!(count_plays(500), MAX_REPEAT);
+    }
+
+    #[test]
+    fn test_repeat_stops_on_failure() {
+        let mut plays = 0;
+        let result = play_repeated(3, Duration::ZERO, || {
+            plays += 1;
+            anyhow::bail!("afplay missing")
+        });
+
+        assert!(result.is_err());
+        assert_eq!(plays, 1);
+    }
+
+    #[test]
+    fn test_play_until_cancelled_preset_flag_plays_nothing() {
+        let cancel = AtomicBool::new(true);
+        let plays = play_until_cancelled(&cancel, 10, Duration::ZERO, || Ok(())).unwrap();
+        assert_eq!(plays, 0);
+    }
+
+    #[test]
+    fn test_play_until_cancelled_stops_when_flag_set() {
+        let cancel = AtomicBool::new(false);
+        let mut calls = 0;
+        let plays = play_until_cancelled(&cancel, 10, Duration::ZERO, || {
+            calls += 1;
+            if calls == 3 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(plays, 3);
+    }
+
+    #[test]
+    fn test_play_until_cancelled_respects_cap() {
+        let cancel = AtomicBool::new(false);
+        let plays = play_until_cancelled(&cancel, 4, Duration::ZERO, || Ok(())).unwrap();
+        assert_eq!(plays, 4);
+    }
+
+    #[test]
+    fn test_play_until_cancelled_from_another_thread() {
+        let cancel = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let handle = scope.spawn(|| {
+                play_until_cancelled(&cancel, u32::MAX, Duration::from_millis(1), || Ok(()))
+            });
+            thread::sleep(Duration::from_millis(20));
+            cancel.store(true, Ordering::Relaxed);
+            assert!(handle.join().unwrap().unwrap() < u32::MAX);
+        });
+    }
+
+    #[test]
+    fn test_is_supported_audio() {
+        assert!(is_supported_audio("/System/Library/Sounds/Glass.aiff"));
+        assert!(is_supported_audio("./alert.wav"));
+        assert!(is_supported_audio("~/Music/alert.M4A"));
+        assert!(is_supported_audio("chime.mp3"));
+        assert!(is_supported_audio("chime.caf"));
+
+        assert!(!is_supported_audio("./alert.ogg"));
+        assert!(!is_supported_audio("./alert.flac"));
+    }
+
+    #[test]
+    fn test_is_supported_audio_without_extension() {
+        assert!(!is_supported_audio("/tmp/alert"));
+        assert!(!is_supported_audio("/tmp/.hidden"));
+        assert!(!is_supported_audio(""));
     }
 }
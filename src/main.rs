@@ -1,32 +1,1048 @@
 use anyhow::Result;
-use clap::{Arg, Command};
-use claude_code_notification::{main as notification_main, Sound};
-use std::io;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use claude_code_notification::backend::{self, Backend};
+use claude_code_notification::classify::Severity;
+use claude_code_notification::config::{self, Config, Source};
+use claude_code_notification::format::{PathDisplay, TruncateMode};
+use claude_code_notification::history;
+use claude_code_notification::i18n;
+use claude_code_notification::input::{self, DEFAULT_MAX_INPUT_BYTES};
+use claude_code_notification::output::OutputFormat;
+use claude_code_notification::paths;
+use claude_code_notification::push::{PushProvider, PushTarget};
+use claude_code_notification::spool;
+use claude_code_notification::state;
+use claude_code_notification::warn::DIAGNOSTIC_PREFIX;
+use claude_code_notification::{
+    bench_send, flush_spooled, parse_delay, read_sound_name, run_with_suppression,
+    split_player_args, suppression_exit_code, suppression_reason, Diagnostics, FailurePolicy,
+    FieldMap, NotificationOptions, Sound, SoundPolicy, SoundTiming, WarningSink,
+};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 
+mod doctor;
 mod setup;
+mod style;
 
 fn main() -> Result<()> {
     let matches = Command::new("claude-code-notification")
         .version(env!("CARGO_PKG_VERSION"))
         .about("Claude Code hook for displaying desktop notifications")
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format: a desktop notification, plain or json on stdout, an osc9 terminal escape, or only the terminal bell")
+                .value_parser(["desktop", "plain", "json", "osc9", "bell"])
+                .default_value("desktop"),
+        )
+        .arg(
+            Arg::new("fallback-stdout")
+                .long("fallback-stdout")
+                .help("Print the notification to stdout when no notification server is available")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Print what the notifier is doing to stderr; repeat for more detail")
+                .action(ArgAction::Count),
+        )
+        .arg(
+            Arg::new("bell")
+                .long("bell")
+                .help("Also ring the terminal bell; use --format bell to ring it instead")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .value_name("MS")
+                .help("How long the notification stays up in milliseconds, where supported; overrides the config file's timeout_ms, and a payload's timeout_ms overrides it")
+                .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("backend")
+                .long("backend")
+                .value_name("BACKEND")
+                .help("How to display the notification")
+                .value_parser(["auto", "notify-rust", "osascript", "terminal-notifier"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .value_name("STYLE")
+                .help("Show a banner that auto-dismisses or an alert that stays until dismissed")
+                .value_parser(["banner", "alert"])
+                .default_value("banner"),
+        )
+        .arg(
+            Arg::new("category")
+                .long("category")
+                .value_name("CATEGORY")
+                .help("Freedesktop notification category hint, Linux only [default: im.received]"),
+        )
+        .arg(
+            Arg::new("hint")
+                .long("hint")
+                .value_name("KEY=VALUE")
+                .help("Extra freedesktop notification hint, Linux only; may be repeated")
+                .value_parser(backend::parse_hint)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("webhook")
+                .long("webhook")
+                .value_name("URL")
+                .help("POST the notification as JSON to URL instead of showing it"),
+        )
+        .arg(
+            Arg::new("webhook-also")
+                .long("webhook-also")
+                .help("Show the desktop notification as well as posting to --webhook")
+                .action(ArgAction::SetTrue)
+                .requires("webhook"),
+        )
+        .arg(
+            Arg::new("push")
+                .long("push")
+                .value_name("SERVICE")
+                .help("Also send each notification to a mobile push service")
+                .value_parser(["ntfy", "pushover"]),
+        )
+        .arg(
+            Arg::new("as-reminder")
+                .long("as-reminder")
+                .help("Add the message to macOS Reminders instead of showing a notification")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("reminder-due")
+                .long("reminder-due")
+                .value_name("MINUTES")
+                .help("Make the --as-reminder reminder due this many minutes from now")
+                .value_parser(clap::value_parser!(u32))
+                .requires("as-reminder"),
+        )
+        .arg(
+            Arg::new("group-key")
+                .long("group-key")
+                .value_name("KEY")
+                .help("Group notifications by this key instead of by session, where supported"),
+        )
+        .arg(
+            Arg::new("action-open-url")
+                .long("action-open-url")
+                .value_name("URL")
+                .help("Open an http(s) or file URL when the notification is clicked")
+                .value_parser(|url: &str| {
+                    backend::validate_action_url(url).map(|_| url.to_string())
+                }),
+        )
+        .arg(
+            Arg::new("icon")
+                .long("icon")
+                .value_name("ICON")
+                .help("Icon name from the icon theme, or a path to an image"),
+        )
+        .arg(
+            Arg::new("icon-from-theme")
+                .long("icon-from-theme")
+                .help("Pick an error, success, or information icon based on the message")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("sound")
                 .long("sound")
                 .value_name("SOUND_NAME")
-                .help("System sound to play with notification")
+                .help("System sound to play with notification [default: Glass]"),
+        )
+        .arg(
+            Arg::new("sound-from")
+                .long("sound-from")
+                .value_name("PATH")
+                .help("Read the sound name from a file or FIFO, overriding --sound")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("sound-on")
+                .long("sound-on")
+                .value_name("POLICY")
+                .help("When to play the sound: always, error, or never [default: always]")
+                .value_parser(["always", "error", "never"]),
+        )
+        .arg(
+            Arg::new("title")
+                .long("title")
+                .value_name("TITLE")
+                .help("Title to use when the input doesn't provide one [default: Claude Code]"),
+        )
+        .arg(
+            Arg::new("player-bin")
+                .long("player-bin")
+                .value_name("PATH")
+                .help("Command used to play sounds, taking afplay's arguments [default: afplay]"),
+        )
+        .arg(
+            Arg::new("player-args")
+                .long("player-args")
+                .value_name("ARGS")
+                .help("Extra arguments for the player, split like a shell command line (e.g. \"-r 1.5\")")
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::new("sound-fallback")
+                .long("sound-fallback")
+                .value_name("NAME")
+                .help("Sound to play when the chosen sound's file is missing")
                 .default_value("Glass"),
         )
-        .subcommand(Command::new("setup").about("Configure Claude Code settings for notifications"))
+        .arg(
+            Arg::new("sound-dir")
+                .long("sound-dir")
+                .value_name("PATH")
+                .help("Directory for bare sound names [default: /System/Library/Sounds]")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("volume")
+                .long("volume")
+                .value_name("MULTIPLIER")
+                .help("Volume multiplier, applied on top of any per-sound volume from the config file")
+                .value_parser(clap::value_parser!(f32)),
+        )
+        .arg(
+            Arg::new("repeat")
+                .long("repeat")
+                .value_name("N")
+                .help("Play the sound N times (capped at 10)")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("sync-sound")
+                .long("sync-sound")
+                .value_name("WHEN")
+                .help("Play the sound synchronously before or after showing the notification")
+                .value_parser(["before", "after"])
+                .num_args(0..=1)
+                .default_missing_value("before"),
+        )
+        .arg(
+            Arg::new("no-wait-sound")
+                .long("no-wait-sound")
+                .help("Start the sound in the background and exit without waiting for it (plays once)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("sync-sound"),
+        )
+        .arg(
+            Arg::new("sound-loop-until-dismissed")
+                .long("sound-loop-until-dismissed")
+                .help("Keep replaying the sound until the notification is dismissed (freedesktop only)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["sync-sound", "no-wait-sound"]),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the notification instead of showing it, without sound, delivery, or state changes")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("strict-sound")
+                .long("strict-sound")
+                .help("Exit with an error if the sound fails to play")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("on-failure")
+                .long("on-failure")
+                .value_name("POLICY")
+                .help("Exit with an error when either the notification or the sound failed (fail-any), only when both did (fail-all), or never")
+                .value_parser(["fail-any", "fail-all", "never"])
+                .conflicts_with("strict-sound"),
+        )
+        .arg(
+            Arg::new("strip-markdown")
+                .long("strip-markdown")
+                .help("Strip markdown formatting from the notification body")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-sanitize")
+                .long("no-sanitize")
+                .help("Keep ANSI escape codes and control characters in the message")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-width")
+                .long("max-width")
+                .value_name("COLUMNS")
+                .help("Wrap the notification body at this many columns (0 disables wrapping)")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("summary-lines")
+                .long("summary-lines")
+                .value_name("N")
+                .help("Show only the first N non-empty lines of the message (0 shows all)")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("title-max-len")
+                .long("title-max-len")
+                .value_name("N")
+                .help("Truncate titles longer than N characters with an ellipsis (0 disables)")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("64"),
+        )
+        .arg(
+            Arg::new("truncate-mode")
+                .long("truncate-mode")
+                .value_name("MODE")
+                .help("Cut long titles at any character or only at a word boundary")
+                .value_parser(["char", "word"])
+                .default_value("char"),
+        )
+        .arg(
+            Arg::new("started-at")
+                .long("started-at")
+                .value_name("UNIX_SECONDS")
+                .help("When the task started; appends the elapsed time to the body")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("show-elapsed")
+                .long("show-elapsed")
+                .help("Append the elapsed time since the transcript was created")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("show-version")
+                .long("show-version")
+                .help("Append the claude-code-notification version to the body")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("show-transcript")
+                .long("show-transcript")
+                .value_name("MODE")
+                .help("Show the transcript path in the body: full, short, or basename")
+                .value_parser(["full", "short", "basename"])
+                .num_args(0..=1)
+                .default_missing_value("short"),
+        )
+        .arg(
+            Arg::new("transcript-summary")
+                .long("transcript-summary")
+                .help("Append tool call and turn counts from the transcript, e.g. \"12 tool calls, 3 turns\"")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("transcript-front-matter")
+                .long("transcript-front-matter")
+                .help("Take sound: and title: overrides from a --- block at the start of the transcript")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("hook-event")
+                .long("hook-event")
+                .value_name("NAME")
+                .help("Hook event that triggered the notification (e.g. Stop, Notification)"),
+        )
+        .arg(
+            Arg::new("from-file")
+                .long("from-file")
+                .value_name("PATH")
+                .help("Read the JSON input from a file instead of stdin")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("max-input-bytes")
+                .long("max-input-bytes")
+                .value_name("BYTES")
+                .help("Fail if stdin is larger than this many bytes, 0 disables the limit [default: 1 MiB]")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("message-field")
+                .long("message-field")
+                .value_name("KEY")
+                .help("Input JSON key to read the message from [default: message]"),
+        )
+        .arg(
+            Arg::new("title-field")
+                .long("title-field")
+                .value_name("KEY")
+                .help("Input JSON key to read the title from [default: title]"),
+        )
+        .arg(
+            Arg::new("session-field")
+                .long("session-field")
+                .value_name("KEY")
+                .help("Input JSON key to read the session id from [default: session_id]"),
+        )
+        .arg(
+            Arg::new("transcript-field")
+                .long("transcript-field")
+                .value_name("KEY")
+                .help("Input JSON key to read the transcript path from [default: transcript_path]"),
+        )
+        .arg(
+            Arg::new("once-per-session")
+                .long("once-per-session")
+                .help("Only show the first notification of each session")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("message-template")
+                .long("message-template")
+                .value_name("TEMPLATE")
+                .help("Render the body from a template with {message}, {session}, {session_short}, and {title}; {{ and }} are literal braces"),
+        )
+        .arg(
+            Arg::new("save-card")
+                .long("save-card")
+                .value_name("DIR")
+                .help("Save a PNG card of each notification in DIR (needs the `card` feature)")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("card-qr-base")
+                .long("card-qr-base")
+                .value_name("URL")
+                .help("Add a QR code to saved cards linking to the transcript under URL, where transcripts are served")
+                .requires("save-card"),
+        )
+        .arg(
+            Arg::new("copy")
+                .long("copy")
+                .help("Also copy the message to the clipboard (pbcopy, wl-copy, or xclip)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-notifications-per-session")
+                .long("max-notifications-per-session")
+                .value_name("N")
+                .help("Stop notifying once a session has had N notifications")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("since-last")
+                .long("since-last")
+                .help("Add how long it's been since the session's previous notification to the body")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("delay")
+                .long("delay")
+                .value_name("MS")
+                .help("Wait this many milliseconds after reading the input before notifying")
+                .value_parser(parse_delay)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("reply-file")
+                .long("reply-file")
+                .value_name("PATH")
+                .help("Ask for a reply after notifying and append it to PATH (macOS)")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("spool")
+                .long("spool")
+                .help("Save notifications that fail to deliver and retry them with `flush`")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("validate-input")
+                .long("validate-input")
+                .help("Validate input against the bundled JSON Schema, reporting every violation")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("bench")
+                .long("bench")
+                .value_name("N")
+                .help("Send N synthetic notifications and report their latency")
+                .value_parser(clap::value_parser!(usize))
+                .hide(true),
+        )
+        .arg(
+            Arg::new("bench-silent")
+                .long("bench-silent")
+                .help("Skip the sound while benchmarking")
+                .action(ArgAction::SetTrue)
+                .requires("bench")
+                .hide(true),
+        )
+        .arg(
+            Arg::new("notify-on-empty")
+                .long("notify-on-empty")
+                .value_name("BOOL")
+                .help("Notify for events without a message, using a default one; false skips them")
+                .value_parser(clap::value_parser!(bool))
+                .default_value("true"),
+        )
+        .arg(
+            Arg::new("raw")
+                .long("raw")
+                .help("Treat the input as the plain-text message instead of JSON; the title comes from --title")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["coalesce", "validate-input"]),
+        )
+        .arg(
+            Arg::new("coalesce")
+                .long("coalesce")
+                .help("Accept several JSON inputs (e.g. JSONL) and show one summary notification")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("min-severity")
+                .long("min-severity")
+                .value_name("LEVEL")
+                .help("Skip messages below this severity: info, warn, or error")
+                .value_parser(["info", "warn", "error"])
+                .default_value("info"),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .help("Append each notification to the history log")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(log_file_arg())
+        .arg(
+            Arg::new("exit-on-suppress")
+                .long("exit-on-suppress")
+                .value_name("CODE")
+                .help("Exit with CODE when a filter suppresses the notification")
+                .value_parser(clap::value_parser!(i32))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("count-badge")
+                .long("count-badge")
+                .help("Count notifications in a persistent counter shown after the message text, e.g. \"(3 unread)\", since no backend can set a dock badge (reset with `clear-badge`)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("syslog")
+                .long("syslog")
+                .help("Also write each notification to the system log via `logger`")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("emit-id")
+                .long("emit-id")
+                .help("Print an id for the notification to stdout, also recorded in logs and webhooks")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Notify even if notifications were turned off with `disable`")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("PATH")
+                .help("Config file to load instead of the default location")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .help("Apply the [profiles.NAME] section of the config file (or set CLAUDE_NOTIFICATION_PROFILE)"),
+        )
+        .arg(
+            Arg::new("print-config")
+                .long("print-config")
+                .help("Print the resolved configuration and where each value came from")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("setup")
+                .about("Configure Claude Code settings for notifications")
+                .arg(settings_path_arg())
+                .arg(project_arg())
+                .arg(
+                    Arg::new("event")
+                        .long("event")
+                        .value_name("EVENT")
+                        .help("Register for a tool event instead of Notification; --matcher picks the tools")
+                        .value_parser(["PreToolUse", "PostToolUse"])
+                        .requires("matcher"),
+                )
+                .arg(
+                    Arg::new("matcher")
+                        .long("matcher")
+                        .value_name("PATTERN")
+                        .help("Tools the --event hook runs for, e.g. Bash or Edit|Write")
+                        .requires("event"),
+                ),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Check the settings file's hook configuration without running Claude")
+                .arg(settings_path_arg())
+                .arg(project_arg()),
+        )
+        .subcommand(
+            Command::new("hooks")
+                .about("Inspect the hooks in the settings file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("List every hook and its command, across all events")
+                        .arg(settings_path_arg())
+                        .arg(project_arg())
+                        .arg(
+                            Arg::new("test")
+                                .long("test")
+                                .help("Run each notification hook with a sample payload, printing instead of notifying")
+                                .action(ArgAction::SetTrue),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Check that the notification hook is set up correctly")
+                .arg(settings_path_arg())
+                .arg(project_arg()),
+        )
+        .subcommand(
+            Command::new("flush")
+                .about("Retry notifications saved by --spool, removing the ones delivered"),
+        )
+        .subcommand(Command::new("clear-badge").about("Reset the --count-badge counter"))
+        .subcommand(Command::new("enable").about("Turn notifications back on"))
+        .subcommand(
+            Command::new("disable")
+                .about("Turn off all notifications until `enable` is run (override with --force)"),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Show recently logged notifications")
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .help("Number of entries to show")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print entries as JSON")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(log_file_arg()),
+        )
         .get_matches();
 
     match matches.subcommand() {
-        Some(("setup", _)) => setup::run_setup(),
+        Some(("setup", sub_matches)) => {
+            let tool_hook = sub_matches
+                .get_one::<String>("event")
+                .zip(sub_matches.get_one::<String>("matcher"))
+                .map(|(event, matcher)| (event.as_str(), matcher.as_str()));
+            setup::run_setup(&resolve_settings_path(sub_matches)?, tool_hook)
+        }
+        Some(("validate", sub_matches)) => {
+            doctor::run_validate(&resolve_settings_path(sub_matches)?)
+        }
+        Some(("hooks", sub_matches)) => match sub_matches.subcommand() {
+            Some(("list", list_matches)) => doctor::run_hooks_list(
+                &resolve_settings_path(list_matches)?,
+                list_matches.get_flag("test"),
+            ),
+            _ => unreachable!("clap requires a hooks subcommand"),
+        },
+        Some(("doctor", sub_matches)) => doctor::run_doctor(&resolve_settings_path(sub_matches)?),
+        Some(("history", sub_matches)) => run_history(sub_matches),
+        Some(("clear-badge", _)) => run_clear_badge(),
+        Some(("enable", _)) => run_set_enabled(true),
+        Some(("disable", _)) => run_set_enabled(false),
         _ => {
-            let sound_name = matches.get_one::<String>("sound").unwrap();
-            let sound = Sound::from_name(sound_name);
+            // The system-wide config sits under the user's, or the one
+            // given with --config
+            let system_config_path = Path::new(config::SYSTEM_CONFIG_PATH);
+            let (config_path, file_config) = match matches.get_one::<PathBuf>("config") {
+                Some(path) => {
+                    let config = config::load_explicit_config(path)?;
+                    let config = match config::load_config(system_config_path)? {
+                        Some(system) => config::merge_configs(system, config),
+                        None => config,
+                    };
+                    (Some(path.clone()), Some(config))
+                }
+                None => {
+                    let path = config::default_config_path();
+                    let config = config::load_layered_config(system_config_path, path.as_deref())?;
+                    (path, config)
+                }
+            };
+            let profile = matches
+                .get_one::<String>("profile")
+                .cloned()
+                .or_else(|| std::env::var("CLAUDE_NOTIFICATION_PROFILE").ok())
+                .filter(|name| !name.is_empty());
+            let file_config = match profile.as_deref() {
+                Some(name) => Some(config::resolve_profile(
+                    &file_config.unwrap_or_default(),
+                    Some(name),
+                )?),
+                None => file_config,
+            };
+            let file_config = match (file_config, paths::cache_dir()) {
+                (Some(config), Some(cache_dir)) => Some(config::apply_custom_sound(
+                    config,
+                    &cache_dir.join("sounds").join("embedded"),
+                )?),
+                (config, _) => config,
+            };
+            let env_config = Config::from_env(|key| std::env::var(key).ok());
+            let warnings = WarningSink::default();
+            // A sound read from --sound-from wins; if it can't be read, warn
+            // and fall back to --sound
+            let sound_from = matches.get_one::<PathBuf>("sound-from").and_then(|path| {
+                read_sound_name(path)
+                    .map_err(|e| warnings.warn(&format!("{:#}", e)))
+                    .ok()
+            });
+            let cli_config = Config {
+                sound: sound_from.or_else(|| matches.get_one::<String>("sound").cloned()),
+                sound_on: matches
+                    .get_one::<String>("sound-on")
+                    .and_then(|name| SoundPolicy::from_name(name)),
+                title: matches.get_one::<String>("title").cloned(),
+                player: matches.get_one::<String>("player-bin").cloned(),
+                sound_dir: matches.get_one::<PathBuf>("sound-dir").cloned(),
+                timeout_ms: matches.get_one::<u32>("timeout").copied(),
+                ..Default::default()
+            };
 
-            let stdin = io::stdin();
-            notification_main(stdin, sound)
+            let (settings, sources) =
+                config::resolve_config(file_config.as_ref(), &env_config, &cli_config);
+
+            if matches.get_flag("print-config") {
+                if system_config_path.exists() {
+                    println!("# system config file: {}", system_config_path.display());
+                }
+                if let Some(path) = &config_path {
+                    println!("# config file: {}", path.display());
+                }
+                println!("{}", config::describe(&settings, &sources));
+                return Ok(());
+            }
+
+            let push = match matches
+                .get_one::<String>("push")
+                .and_then(|name| PushProvider::from_name(name))
+            {
+                Some(provider) => Some(PushTarget::new(provider, &settings.push)?),
+                None => None,
+            };
+
+            let options = NotificationOptions {
+                sound: Sound::from_name(&settings.sound),
+                sound_policy: settings.sound_on,
+                sound_timing: if matches.get_flag("no-wait-sound") {
+                    SoundTiming::Detached
+                } else {
+                    matches
+                        .get_one::<String>("sync-sound")
+                        .and_then(|when| SoundTiming::from_name(when))
+                        .unwrap_or_default()
+                },
+                // An unconfigured title is left for the notifier to localize
+                title: (sources.get("title") != Some(&Source::Default)).then_some(settings.title),
+                volume: matches.get_one::<f32>("volume").copied(),
+                volumes: settings.volumes,
+                session_sounds: settings.sessions,
+                event_sounds: settings.event_sounds,
+                quiet_hours: settings.quiet_hours,
+                repeat: *matches.get_one::<u32>("repeat").unwrap(),
+                player: Some(settings.player),
+                sound_fallback: Sound::from_name(
+                    matches.get_one::<String>("sound-fallback").unwrap(),
+                ),
+                player_args: matches
+                    .get_one::<String>("player-args")
+                    .map(|args| split_player_args(args))
+                    .unwrap_or_default(),
+                sound_dir: Some(settings.sound_dir),
+                strip_markdown: matches.get_flag("strip-markdown"),
+                keep_control_chars: matches.get_flag("no-sanitize"),
+                max_width: *matches.get_one::<usize>("max-width").unwrap(),
+                title_max_len: *matches.get_one::<usize>("title-max-len").unwrap(),
+                truncate_mode: matches
+                    .get_one::<String>("truncate-mode")
+                    .and_then(|name| TruncateMode::from_name(name))
+                    .unwrap_or_default(),
+                summary_lines: *matches.get_one::<usize>("summary-lines").unwrap(),
+                strict_sound: matches.get_flag("strict-sound"),
+                force: matches.get_flag("force"),
+                on_failure: matches
+                    .get_one::<String>("on-failure")
+                    .and_then(|policy| FailurePolicy::from_name(policy)),
+                hook_event: matches.get_one::<String>("hook-event").cloned(),
+                started_at: matches
+                    .get_one::<u64>("started-at")
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(*secs)),
+                show_elapsed: matches.get_flag("show-elapsed"),
+                show_version: matches.get_flag("show-version"),
+                emit_id: matches.get_flag("emit-id"),
+                syslog: matches.get_flag("syslog"),
+                count_badge: matches.get_flag("count-badge"),
+                log_file: resolve_log_file(&matches, matches.get_flag("log")),
+                once_per_session: matches.get_flag("once-per-session"),
+                max_per_session: matches
+                    .get_one::<u32>("max-notifications-per-session")
+                    .copied(),
+                since_last: matches.get_flag("since-last"),
+                message_template: matches.get_one::<String>("message-template").cloned(),
+                copy: matches.get_flag("copy"),
+                save_card: matches.get_one::<PathBuf>("save-card").cloned(),
+                card_qr_base: matches.get_one::<String>("card-qr-base").cloned(),
+                max_input_bytes: matches
+                    .get_one::<usize>("max-input-bytes")
+                    .copied()
+                    .unwrap_or(DEFAULT_MAX_INPUT_BYTES),
+                coalesce: matches.get_flag("coalesce"),
+                raw: matches.get_flag("raw"),
+                skip_empty: !*matches.get_one::<bool>("notify-on-empty").unwrap(),
+                loop_until_dismissed: matches.get_flag("sound-loop-until-dismissed"),
+                min_severity: matches
+                    .get_one::<String>("min-severity")
+                    .and_then(|name| Severity::from_name(name))
+                    .unwrap_or_default(),
+                backend: matches
+                    .get_one::<String>("backend")
+                    .and_then(|name| Backend::from_name(name))
+                    .unwrap_or_default(),
+                style: matches
+                    .get_one::<String>("style")
+                    .and_then(|name| backend::NotificationStyle::from_name(name))
+                    .unwrap_or_default(),
+                category: matches.get_one::<String>("category").cloned(),
+                hints: matches
+                    .get_many::<(String, backend::HintValue)>("hint")
+                    .map(|hints| hints.cloned().collect())
+                    .unwrap_or_default(),
+                icon: matches.get_one::<String>("icon").cloned(),
+                action_url: matches.get_one::<String>("action-open-url").cloned(),
+                group_key: matches.get_one::<String>("group-key").cloned(),
+                as_reminder: matches.get_flag("as-reminder"),
+                reminder_due: matches.get_one::<u32>("reminder-due").copied(),
+                webhook: matches.get_one::<String>("webhook").cloned(),
+                webhook_also: matches.get_flag("webhook-also"),
+                push,
+                warnings,
+                locale: Some(i18n::locale_from_env(|key| std::env::var(key).ok())),
+                icon_from_theme: matches.get_flag("icon-from-theme"),
+                format: matches
+                    .get_one::<String>("format")
+                    .and_then(|name| OutputFormat::from_name(name))
+                    .unwrap_or_default(),
+                sink: None,
+                fallback_stdout: matches.get_flag("fallback-stdout"),
+                bell: matches.get_flag("bell"),
+                diagnostics: Diagnostics::new(
+                    matches.get_count("verbose"),
+                    WarningSink::Stderr(DIAGNOSTIC_PREFIX.to_string()),
+                ),
+                timeout_ms: settings.timeout_ms,
+                delay: *matches.get_one::<Duration>("delay").unwrap(),
+                reply_file: matches.get_one::<PathBuf>("reply-file").cloned(),
+                spool_dir: if matches.get_flag("spool") {
+                    spool::spool_dir()
+                } else {
+                    None
+                },
+                transcript_display: matches
+                    .get_one::<String>("show-transcript")
+                    .and_then(|mode| PathDisplay::from_name(mode)),
+                transcript_summary: matches.get_flag("transcript-summary"),
+                transcript_front_matter: matches.get_flag("transcript-front-matter"),
+                validate_input: matches.get_flag("validate-input"),
+                field_map: FieldMap {
+                    session_id: matches.get_one::<String>("session-field").cloned(),
+                    transcript_path: matches.get_one::<String>("transcript-field").cloned(),
+                    message: matches.get_one::<String>("message-field").cloned(),
+                    title: matches.get_one::<String>("title-field").cloned(),
+                },
+            };
+            let options = if matches.get_flag("dry-run") {
+                options.dry_run()
+            } else {
+                options
+            };
+
+            // `flush` delivers with the options resolved above
+            if matches.subcommand_name() == Some("flush") {
+                return run_flush(&options);
+            }
+
+            if let Some(&runs) = matches.get_one::<usize>("bench") {
+                let stats = bench_send(&options, runs, matches.get_flag("bench-silent"))?;
+                println!(
+                    "{} notifications: min {:.1?}, max {:.1?}, mean {:.1?}",
+                    stats.runs, stats.min, stats.max, stats.mean
+                );
+                return Ok(());
+            }
+
+            // A disabled notifier exits without reading its input
+            let disabled = suppression_reason(None, &options, chrono::Local::now().time())?;
+            let suppressed = match disabled {
+                Some(reason) => Some(reason),
+                None => {
+                    if !matches.contains_id("from-file") {
+                        print_setup_hint();
+                    }
+                    let input = input::open_input(
+                        matches
+                            .get_one::<PathBuf>("from-file")
+                            .map(PathBuf::as_path),
+                    )?;
+                    run_with_suppression(input, &options)?
+                }
+            };
+
+            let code = suppression_exit_code(
+                suppressed,
+                *matches.get_one::<i32>("exit-on-suppress").unwrap(),
+            );
+            if code != 0 {
+                std::process::exit(code);
+            }
+            Ok(())
         }
     }
 }
+
+/// Points someone running the notifier by hand, before they've installed
+/// the hook, at `setup`.
+fn print_setup_hint() {
+    let interactive = std::io::stdin().is_terminal();
+    if setup::resolve_settings_path(None, false, Path::new("."))
+        .is_ok_and(|path| setup::needs_setup_hint(&path, interactive))
+    {
+        eprintln!(
+            "{}",
+            style::warn(
+                "💡 No notification hook found; run `claude-code-notification setup` to install one"
+            )
+        );
+    }
+}
+
+fn settings_path_arg() -> Arg {
+    Arg::new("settings-path")
+        .long("settings-path")
+        .value_name("PATH")
+        .help("Claude Code settings file [default: ~/.claude/settings.json]")
+        .value_parser(clap::value_parser!(PathBuf))
+        .conflicts_with("project")
+}
+
+fn project_arg() -> Arg {
+    Arg::new("project")
+        .long("project")
+        .help("Use the project's ./.claude/settings.json")
+        .action(ArgAction::SetTrue)
+}
+
+fn resolve_settings_path(matches: &ArgMatches) -> Result<PathBuf> {
+    setup::resolve_settings_path(
+        matches
+            .get_one::<PathBuf>("settings-path")
+            .map(PathBuf::as_path),
+        matches.get_flag("project"),
+        &std::env::current_dir()?,
+    )
+}
+
+fn log_file_arg() -> Arg {
+    Arg::new("log-file")
+        .long("log-file")
+        .value_name("PATH")
+        .help(
+            "History log location [default: ~/.local/state/claude-code-notification/history.jsonl]",
+        )
+        .value_parser(clap::value_parser!(PathBuf))
+}
+
+/// Returns the history log path when logging is enabled, either explicitly
+/// or by passing `--log-file`.
+fn resolve_log_file(matches: &ArgMatches, enabled: bool) -> Option<PathBuf> {
+    match matches.get_one::<PathBuf>("log-file") {
+        Some(path) => Some(path.clone()),
+        None if enabled => history::default_history_path(),
+        None => None,
+    }
+}
+
+fn run_set_enabled(on: bool) -> Result<()> {
+    let Some(path) = state::disabled_path() else {
+        anyhow::bail!("Could not determine the state directory");
+    };
+    state::set_enabled(&path, on)?;
+
+    println!("Notifications {}", if on { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+fn run_flush(options: &NotificationOptions) -> Result<()> {
+    let Some(dir) = spool::spool_dir() else {
+        anyhow::bail!("Could not determine the state directory");
+    };
+    let delivered = flush_spooled(&dir, options)?;
+
+    println!("Delivered {} spooled notification(s)", delivered);
+    Ok(())
+}
+
+fn run_clear_badge() -> Result<()> {
+    let Some(path) = state::badge_path() else {
+        anyhow::bail!("Could not determine the state directory");
+    };
+    state::clear_badge(&path)?;
+
+    println!("Badge count cleared");
+    Ok(())
+}
+
+fn run_history(matches: &ArgMatches) -> Result<()> {
+    let Some(path) = resolve_log_file(matches, true) else {
+        anyhow::bail!("Could not determine the history log location");
+    };
+    let limit = *matches.get_one::<usize>("limit").unwrap();
+
+    let events = if path.exists() {
+        history::read_history(&path, limit)?
+    } else {
+        Vec::new()
+    };
+
+    if matches.get_flag("json") {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+    } else if events.is_empty() {
+        println!("No notifications logged yet ({})", path.display());
+    } else {
+        println!("{}", history::format_table(&events));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,637 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::SystemTime;
+
+use crate::backend::{
+    show_and_wait, supports_dismissal, HintValue, NotificationStyle, DEFAULT_CATEGORY,
+};
+use crate::output::OutputFormat;
+use crate::{
+    classify, effective_volume, format, handle_delivery_results, i18n, loop_sound, play_sound,
+    should_play_sound, spawn_sound, NotificationOptions, NotificationSink, Playback, Sound,
+    SoundPolicy, SoundTiming, SystemSink, WarningSink, DEFAULT_PLAYER, SYSTEM_SOUNDS_DIR,
+};
+
+/// A notification with all options applied, ready to be displayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedNotification {
+    pub title: String,
+    pub body: String,
+    /// The sound to play, or `None` if the sound policy skips it or the
+    /// notification plays it itself.
+    pub sound: Option<Sound>,
+    /// Whether the notification plays the OS's default sound.
+    pub system_sound: bool,
+    /// Freedesktop category hint (only applied on Linux/BSD).
+    pub category: String,
+    /// Extra freedesktop hints from `--hint` (only applied on Linux/BSD).
+    pub hints: Vec<(String, HintValue)>,
+    /// Icon name or path, if any.
+    pub icon: Option<String>,
+    /// URL opened when the notification is clicked, where supported.
+    pub action_url: Option<String>,
+    pub style: NotificationStyle,
+    /// Groups notifications from the same session, where supported.
+    pub thread_id: Option<String>,
+    /// How long the notification stays up, where supported.
+    pub timeout_ms: Option<u32>,
+}
+
+/// Builder for sending a notification without going through stdin.
+///
+/// ```no_run
+/// use claude_code_notification::{Notifier, Sound};
+///
+/// Notifier::new()
+///     .title("Build")
+///     .message("All tests passed")
+///     .sound(Sound::Hero)
+///     .send()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    title: Option<String>,
+    message: String,
+    suffix: String,
+    thread_id: Option<String>,
+    options: NotificationOptions,
+    dry_run: bool,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title, overriding the default from the options.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn message<S: Into<String>>(mut self, message: S) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Sets text appended to the body after sanitizing, markdown stripping,
+    /// and `summary_lines`, such as the transcript path. It isn't used to
+    /// pick the sound or icon.
+    pub fn suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Groups this notification with others sharing the id; see
+    /// [`crate::backend::thread_id_for`].
+    pub fn thread_id<S: Into<String>>(mut self, thread_id: S) -> Self {
+        self.thread_id = Some(thread_id.into());
+        self
+    }
+
+    pub fn sound(mut self, sound: Sound) -> Self {
+        self.options.sound = sound;
+        self
+    }
+
+    pub fn sound_policy(mut self, policy: SoundPolicy) -> Self {
+        self.options.sound_policy = policy;
+        self
+    }
+
+    /// Replaces all options at once, keeping the title and message.
+    pub fn options(mut self, options: NotificationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// When enabled, `send` prepares the notification but doesn't display it
+    /// or play the sound.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// The title the notification will have: the explicit one, the
+    /// configured one, or the localized default, truncated to fit.
+    pub fn resolved_title(&self) -> String {
+        let options = &self.options;
+        let locale = options.locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE);
+        let title = self
+            .title
+            .as_deref()
+            .or(options.title.as_deref())
+            .unwrap_or_else(|| i18n::localized("title", locale));
+        format::truncate(title, options.title_max_len, options.truncate_mode)
+    }
+
+    /// Applies the options to produce the final title, body, and sound.
+    pub fn prepare(&self) -> PreparedNotification {
+        let options = &self.options;
+        let locale = options.locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE);
+
+        let message = if self.message.trim().is_empty() {
+            let event = options.hook_event.as_deref().unwrap_or_default();
+            i18n::localized(i18n::event_message_key(event), locale)
+        } else {
+            &self.message
+        };
+
+        let mut body = if options.keep_control_chars {
+            message.to_string()
+        } else {
+            format::sanitize_message(message)
+        };
+        if options.strip_markdown {
+            body = format::strip_markdown(&body);
+        }
+        body = format::first_n_lines(&body, options.summary_lines);
+        if let Some(started_at) = options.started_at {
+            let elapsed = format::format_elapsed(started_at, SystemTime::now());
+            body.push_str(&format!(" (took {})", elapsed));
+        }
+        body.push_str(&self.suffix);
+        if options.show_version {
+            body = format::append_version(&body);
+        }
+        body = format::wrap_body(&body, options.max_width);
+
+        let sound = should_play_sound(message, options.sound_policy).then(|| options.sound.clone());
+        // The system default sound goes through the notification API
+        let system_sound = sound == Some(Sound::SystemDefault);
+        let sound = sound.filter(|_| !system_sound);
+
+        let icon = options.icon.clone().or_else(|| {
+            options
+                .icon_from_theme
+                .then(|| classify::icon_for_message(message).to_string())
+        });
+
+        PreparedNotification {
+            title: self.resolved_title(),
+            body,
+            sound,
+            system_sound,
+            hints: options.hints.clone(),
+            category: options
+                .category
+                .clone()
+                .unwrap_or_else(|| DEFAULT_CATEGORY.to_string()),
+            icon,
+            action_url: options.action_url.clone(),
+            style: options.style,
+            thread_id: self.thread_id.clone(),
+            timeout_ms: options.timeout_ms,
+        }
+    }
+
+    pub fn send(&self) -> Result<()> {
+        let prepared = self.prepare();
+        let diagnostics = &self.options.diagnostics;
+        diagnostics.log(1, &format!("Resolved title: {}", prepared.title));
+        diagnostics.log(
+            1,
+            &format!(
+                "Resolved sound: {}",
+                match (&prepared.sound, prepared.system_sound) {
+                    (_, true) => "system default",
+                    (Some(sound), false) => sound.as_str(),
+                    (None, false) => "none",
+                }
+            ),
+        );
+        if self.dry_run {
+            return Ok(());
+        }
+
+        let playback = Playback {
+            volume: effective_volume(
+                &self.options.sound,
+                self.options.volume,
+                &self.options.volumes,
+            ),
+            repeat: self.options.repeat,
+            player: self
+                .options
+                .player
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PLAYER.to_string()),
+            extra_args: self.options.player_args.clone(),
+            fallback: self.options.sound_fallback.clone(),
+            warnings: self.options.warnings.clone(),
+            sound_dir: self
+                .options
+                .sound_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(SYSTEM_SOUNDS_DIR)),
+        };
+
+        // Looping needs to know when the real notification is dismissed
+        if self.options.loop_until_dismissed && self.options.sink.is_none() {
+            if let Some(sound) = &prepared.sound {
+                if self.options.format == OutputFormat::Desktop
+                    && supports_dismissal(self.options.backend)
+                {
+                    let (notification_result, sound_result) =
+                        deliver_looping(&prepared, sound, &playback, &self.options.warnings);
+                    return handle_delivery_results(
+                        notification_result,
+                        sound_result,
+                        true,
+                        &self.options,
+                    );
+                }
+                self.options
+                    .warnings
+                    .warn("This backend doesn't report dismissal; playing the sound normally");
+            }
+        }
+
+        let (notification_result, sound_result) = deliver(
+            &prepared,
+            self.options.sound_timing,
+            |sound| {
+                diagnostics.log(
+                    1,
+                    &format!("Playing sound {} with {}", sound.as_str(), playback.player),
+                );
+                let result = match self.options.sound_timing {
+                    SoundTiming::Detached => spawn_sound(sound, &playback),
+                    _ => play_sound(sound, &playback).map(|_| ()),
+                };
+                diagnostics.log(2, &format!("Sound result: {:?}", result));
+                result
+            },
+            |prepared| {
+                diagnostics.log(
+                    1,
+                    &match (&self.options.sink, self.options.format) {
+                        (Some(sink), _) => format!("Showing notification via {:?}", sink),
+                        (None, OutputFormat::Desktop) => {
+                            format!("Showing notification via {}", self.options.backend.as_str())
+                        }
+                        (None, format) => format!("Writing notification as {}", format.as_str()),
+                    },
+                );
+                let result = match &self.options.sink {
+                    Some(sink) => sink.show(prepared),
+                    None => SystemSink {
+                        format: self.options.format,
+                        backend: self.options.backend,
+                        warnings: self.options.warnings.clone(),
+                    }
+                    .show(prepared),
+                };
+                diagnostics.log(2, &format!("Notification result: {:?}", result));
+                result
+            },
+        );
+
+        handle_delivery_results(
+            notification_result,
+            sound_result,
+            prepared.sound.is_some(),
+            &self.options,
+        )
+    }
+}
+
+/// Loops the sound on a background thread while the notification is shown,
+/// stopping once it is dismissed.
+fn deliver_looping(
+    prepared: &PreparedNotification,
+    sound: &Sound,
+    playback: &Playback,
+    warnings: &WarningSink,
+) -> (Result<()>, Result<()>) {
+    let cancel = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        let sound_handle = scope.spawn(|| loop_sound(sound, playback, &cancel));
+
+        let notification_result = show_and_wait(prepared, warnings);
+        cancel.store(true, Ordering::Relaxed);
+
+        let sound_result = sound_handle
+            .join()
+            .unwrap_or_else(|e| Err(anyhow::anyhow!("Sound thread panicked: {:?}", e)));
+
+        (notification_result, sound_result)
+    })
+}
+
+/// Shows the notification and plays its sound using the given timing,
+/// returning the notification and sound results.
+fn deliver<P, S>(
+    prepared: &PreparedNotification,
+    timing: SoundTiming,
+    play: P,
+    show: S,
+) -> (Result<()>, Result<()>)
+where
+    P: FnOnce(&Sound) -> Result<()> + Send,
+    S: FnOnce(&PreparedNotification) -> Result<()>,
+{
+    let Some(sound) = &prepared.sound else {
+        return (show(prepared), Ok(()));
+    };
+
+    match timing {
+        SoundTiming::Parallel => thread::scope(|scope| {
+            // Spawn a thread to play the sound in parallel
+            let sound_handle = scope.spawn(|| play(sound));
+
+            // Show the notification (this happens in parallel with sound)
+            let notification_result = show(prepared);
+
+            // Wait for the sound thread to complete
+            let sound_result = sound_handle
+                .join()
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("Sound thread panicked: {:?}", e)));
+
+            (notification_result, sound_result)
+        }),
+        SoundTiming::Before => {
+            let sound_result = play(sound);
+            (show(prepared), sound_result)
+        }
+        SoundTiming::After => {
+            let notification_result = show(prepared);
+            (notification_result, play(sound))
+        }
+        SoundTiming::Detached => {
+            // `play` only spawns the player, so there is nothing to join
+            let sound_result = play(sound);
+            (show(prepared), sound_result)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_TITLE;
+    use std::sync::Mutex;
+
+    fn record_delivery(timing: SoundTiming) -> Vec<&'static str> {
+        let calls = Mutex::new(Vec::new());
+        let prepared = Notifier::new().message("Done").prepare();
+
+        let (notification_result, sound_result) = deliver(
+            &prepared,
+            timing,
+            |_| {
+                calls.lock().unwrap().push("play");
+                Ok(())
+            },
+            |_| {
+                calls.lock().unwrap().push("show");
+                Ok(())
+            },
+        );
+
+        assert!(notification_result.is_ok());
+        assert!(sound_result.is_ok());
+        calls.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_builder_fields() {
+        let prepared = Notifier::new()
+            .title("Build")
+            .message("All tests passed")
+            .sound(Sound::Hero)
+            .prepare();
+
+        assert_eq!(prepared.title, "Build");
+        assert_eq!(prepared.body, "All tests passed");
+        assert!(matches!(prepared.sound, Some(Sound::Hero)));
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let prepared = Notifier::new().message("Hello").prepare();
+
+        assert_eq!(prepared.title, DEFAULT_TITLE);
+        assert!(matches!(prepared.sound, Some(Sound::Glass)));
+    }
+
+    #[test]
+    fn test_builder_applies_options() {
+        let options = NotificationOptions {
+            title: Some("Fallback".to_string()),
+            sound_policy: SoundPolicy::Error,
+            strip_markdown: true,
+            hook_event: Some("Stop".to_string()),
+            ..Default::default()
+        };
+
+        let prepared = Notifier::new()
+            .options(options.clone())
+            .message("**Done**")
+            .prepare();
+        assert_eq!(prepared.title, "Fallback");
+        assert_eq!(prepared.body, "Done");
+        assert_eq!(prepared.sound, None);
+
+        let prepared = Notifier::new().options(options).prepare();
+        assert_eq!(prepared.body, "Claude finished");
+    }
+
+    #[test]
+    fn test_system_default_sound_skips_player() {
+        let prepared = Notifier::new()
+            .message("Done")
+            .sound(Sound::SystemDefault)
+            .prepare();
+        assert_eq!(prepared.sound, None);
+        assert!(prepared.system_sound);
+
+        let prepared = Notifier::new().message("Done").prepare();
+        assert_eq!(prepared.sound, Some(Sound::Glass));
+        assert!(!prepared.system_sound);
+
+        let prepared = Notifier::new()
+            .message("Done")
+            .sound(Sound::SystemDefault)
+            .sound_policy(SoundPolicy::Never)
+            .prepare();
+        assert!(!prepared.system_sound);
+    }
+
+    #[test]
+    fn test_title_max_len() {
+        let options = NotificationOptions {
+            title_max_len: 8,
+            ..Default::default()
+        };
+
+        let prepared = Notifier::new()
+            .options(options.clone())
+            .title("Refactor the parser")
+            .prepare();
+        assert_eq!(prepared.title, "Refacto…");
+
+        let prepared = Notifier::new().options(options).title("Build").prepare();
+        assert_eq!(prepared.title, "Build");
+    }
+
+    #[test]
+    fn test_resolved_title_matches_prepare() {
+        let options = NotificationOptions {
+            title: Some("Configured".to_string()),
+            title_max_len: 8,
+            ..Default::default()
+        };
+
+        let notifier = Notifier::new().options(options.clone()).message("Done");
+        assert_eq!(notifier.resolved_title(), "Configu…");
+        assert_eq!(notifier.resolved_title(), notifier.prepare().title);
+
+        let notifier = Notifier::new().options(options).title("Build");
+        assert_eq!(notifier.resolved_title(), "Build");
+        assert_eq!(Notifier::new().resolved_title(), DEFAULT_TITLE);
+    }
+
+    #[test]
+    fn test_suffix_skips_body_transforms() {
+        let options = NotificationOptions {
+            summary_lines: 1,
+            strip_markdown: true,
+            sound_policy: SoundPolicy::Error,
+            ..Default::default()
+        };
+
+        let prepared = Notifier::new()
+            .options(options)
+            .message("line1\nline2")
+            .suffix("\n📄 ~/error-fix/__t__.jsonl")
+            .prepare();
+
+        assert_eq!(prepared.body, "line1…\n📄 ~/error-fix/__t__.jsonl");
+        assert!(prepared.sound.is_none());
+    }
+
+    #[test]
+    fn test_suffix_follows_elapsed_time() {
+        let options = NotificationOptions {
+            summary_lines: 1,
+            started_at: Some(SystemTime::now()),
+            ..Default::default()
+        };
+
+        let prepared = Notifier::new()
+            .options(options)
+            .message("Done\nmore")
+            .suffix(" (4m10s since last)")
+            .prepare();
+
+        assert!(prepared.body.starts_with("Done… (took "));
+        assert!(prepared.body.ends_with(") (4m10s since last)"));
+    }
+
+    #[test]
+    fn test_dry_run_send() {
+        let result = Notifier::new()
+            .message("Dry run")
+            .sound(Sound::Custom("/nonexistent/file.wav".to_string()))
+            .dry_run(true)
+            .send();
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_deliver_sync_before_plays_then_shows() {
+        assert_eq!(record_delivery(SoundTiming::Before), vec!["play", "show"]);
+    }
+
+    #[test]
+    fn test_deliver_sync_after_shows_then_plays() {
+        assert_eq!(record_delivery(SoundTiming::After), vec!["show", "play"]);
+    }
+
+    #[test]
+    fn test_deliver_parallel_does_both() {
+        let mut calls = record_delivery(SoundTiming::Parallel);
+        calls.sort();
+        assert_eq!(calls, vec!["play", "show"]);
+    }
+
+    #[test]
+    fn test_deliver_without_sound_only_shows() {
+        let prepared = Notifier::new()
+            .message("Done")
+            .sound_policy(SoundPolicy::Never)
+            .prepare();
+
+        let (notification_result, sound_result) = deliver(
+            &prepared,
+            SoundTiming::Before,
+            |_| panic!("Sound should not play"),
+            |_| Ok(()),
+        );
+
+        assert!(notification_result.is_ok());
+        assert!(sound_result.is_ok());
+    }
+
+    fn sound_thread(timing: SoundTiming) -> thread::ThreadId {
+        let prepared = Notifier::new().message("Done").prepare();
+        let played_on = Mutex::new(None);
+
+        let (notification_result, sound_result) = deliver(
+            &prepared,
+            timing,
+            |_| {
+                *played_on.lock().unwrap() = Some(thread::current().id());
+                Ok(())
+            },
+            |_| Ok(()),
+        );
+
+        assert!(notification_result.is_ok());
+        assert!(sound_result.is_ok());
+        played_on.into_inner().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_deliver_parallel_joins_sound_thread() {
+        assert_ne!(sound_thread(SoundTiming::Parallel), thread::current().id());
+    }
+
+    #[test]
+    fn test_deliver_detached_spawns_without_thread() {
+        assert_eq!(sound_thread(SoundTiming::Detached), thread::current().id());
+        assert_eq!(record_delivery(SoundTiming::Detached), vec!["play", "show"]);
+    }
+
+    #[test]
+    fn test_icon_selection() {
+        let themed = NotificationOptions {
+            icon_from_theme: true,
+            ..Default::default()
+        };
+
+        let prepared = Notifier::new()
+            .options(themed.clone())
+            .message("Build failed")
+            .prepare();
+        assert_eq!(prepared.icon.as_deref(), Some("dialog-error"));
+
+        let explicit = NotificationOptions {
+            icon: Some("/tmp/claude.png".to_string()),
+            ..themed
+        };
+        let prepared = Notifier::new()
+            .options(explicit)
+            .message("Build failed")
+            .prepare();
+        assert_eq!(prepared.icon.as_deref(), Some("/tmp/claude.png"));
+
+        assert_eq!(Notifier::new().message("Done").prepare().icon, None);
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+
+use crate::backend::Backend;
+use crate::PreparedNotification;
+
+/// Where and how the notification is emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A native desktop notification via the selected backend.
+    #[default]
+    Desktop,
+    /// `title: body` printed to stdout.
+    Plain,
+    /// A JSON object printed to stdout.
+    Json,
+    /// An OSC 9 escape sequence written to the controlling terminal, which
+    /// terminals such as iTerm2 and WezTerm display as a notification.
+    Osc9,
+    /// Only the terminal bell, for environments without a notification
+    /// system.
+    Bell,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "desktop" => Some(OutputFormat::Desktop),
+            "plain" => Some(OutputFormat::Plain),
+            "json" => Some(OutputFormat::Json),
+            "osc9" => Some(OutputFormat::Osc9),
+            "bell" => Some(OutputFormat::Bell),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            OutputFormat::Desktop => "desktop",
+            OutputFormat::Plain => "plain",
+            OutputFormat::Json => "json",
+            OutputFormat::Osc9 => "osc9",
+            OutputFormat::Bell => "bell",
+        }
+    }
+}
+
+/// Returns the format to print instead of a desktop notification when
+/// `--fallback-stdout` is set and no notification server is available.
+/// `supported` is only probed for in-process backends showing a desktop
+/// notification; command-based backends report their own failures.
+pub fn stdout_fallback<F: FnOnce() -> bool>(
+    format: OutputFormat,
+    backend: Backend,
+    fallback_stdout: bool,
+    supported: F,
+) -> Option<OutputFormat> {
+    let in_process = matches!(backend, Backend::Auto | Backend::NotifyRust);
+    (fallback_stdout && format == OutputFormat::Desktop && in_process && !supported())
+        .then_some(OutputFormat::Plain)
+}
+
+/// Builds the OSC 9 sequence for `message`. Control characters would end or
+/// corrupt the sequence, so they are replaced with spaces.
+pub fn emit_osc9(message: &str) -> String {
+    let payload: String = message
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    format!("\x1b]9;{}\x07", payload)
+}
+
+/// Writes the BEL control character to `w`.
+pub fn ring_bell<W: Write>(w: &mut W) -> io::Result<()> {
+    w.write_all(b"\x07")?;
+    w.flush()
+}
+
+/// Rings the bell on the controlling terminal. Hooks usually have stdout
+/// captured, so this writes to the terminal directly.
+pub fn ring_terminal_bell() -> io::Result<()> {
+    ring_bell(&mut OpenOptions::new().write(true).open("/dev/tty")?)
+}
+
+/// Renders the notification for the stdout-based formats.
+fn render_stdout(format: OutputFormat, prepared: &PreparedNotification) -> Option<String> {
+    match format {
+        OutputFormat::Plain => Some(format!("{}: {}", prepared.title, prepared.body)),
+        OutputFormat::Json => Some(
+            json!({
+                "title": prepared.title,
+                "body": prepared.body,
+            })
+            .to_string(),
+        ),
+        OutputFormat::Desktop | OutputFormat::Osc9 | OutputFormat::Bell => None,
+    }
+}
+
+/// Emits a notification in a terminal-based format. Returns `false` for
+/// `Desktop`, which the caller displays through a backend instead.
+pub fn emit(format: OutputFormat, prepared: &PreparedNotification) -> Result<bool> {
+    match format {
+        OutputFormat::Desktop => Ok(false),
+        OutputFormat::Osc9 => {
+            // Hooks usually have stdout captured, so write to the terminal
+            let mut tty = OpenOptions::new().write(true).open("/dev/tty")?;
+            let message = format!("{}: {}", prepared.title, prepared.body);
+            tty.write_all(emit_osc9(&message).as_bytes())?;
+            tty.flush()?;
+            Ok(true)
+        }
+        OutputFormat::Bell => {
+            ring_terminal_bell()?;
+            Ok(true)
+        }
+        OutputFormat::Plain | OutputFormat::Json => {
+            if let Some(output) = render_stdout(format, prepared) {
+                println!("{}", output);
+            }
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Notifier;
+
+    #[test]
+    fn test_emit_osc9_bytes() {
+        assert_eq!(emit_osc9("Build done").as_bytes(), b"\x1b]9;Build done\x07");
+    }
+
+    #[test]
+    fn test_emit_osc9_strips_control_characters() {
+        assert_eq!(
+            emit_osc9("line one\nline\x07two\x1b"),
+            "\x1b]9;line one line two \x07"
+        );
+    }
+
+    #[test]
+    fn test_ring_bell_writes_bel() {
+        let mut buffer = Vec::new();
+        ring_bell(&mut buffer).unwrap();
+
+        assert_eq!(buffer, b"\x07");
+    }
+
+    #[test]
+    fn test_render_stdout_formats() {
+        let prepared = Notifier::new().title("Build").message("Done").prepare();
+
+        assert_eq!(
+            render_stdout(OutputFormat::Plain, &prepared).unwrap(),
+            "Build: Done"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_stdout(OutputFormat::Json, &prepared).unwrap()).unwrap();
+        assert_eq!(json["title"], "Build");
+        assert_eq!(json["body"], "Done");
+
+        assert_eq!(render_stdout(OutputFormat::Desktop, &prepared), None);
+    }
+
+    #[test]
+    fn test_stdout_fallback_decision() {
+        let desktop = OutputFormat::Desktop;
+        assert_eq!(
+            stdout_fallback(desktop, Backend::Auto, true, || false),
+            Some(OutputFormat::Plain)
+        );
+        assert_eq!(stdout_fallback(desktop, Backend::Auto, true, || true), None);
+        assert_eq!(
+            stdout_fallback(desktop, Backend::Auto, false, || false),
+            None
+        );
+        assert_eq!(
+            stdout_fallback(desktop, Backend::Osascript, true, || false),
+            None
+        );
+        assert_eq!(
+            stdout_fallback(OutputFormat::Json, Backend::Auto, true, || false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stdout_fallback_skips_probe_when_disabled() {
+        let fallback = stdout_fallback(OutputFormat::Desktop, Backend::Auto, false, || {
+            panic!("should not probe")
+        });
+        assert_eq!(fallback, None);
+    }
+
+    #[test]
+    fn test_stdout_fallback_formatting() {
+        let prepared = Notifier::new()
+            .title("Build")
+            .message("Done\nAll tests passed")
+            .prepare();
+        let format = stdout_fallback(OutputFormat::Desktop, Backend::Auto, true, || false).unwrap();
+
+        assert_eq!(
+            render_stdout(format, &prepared).unwrap(),
+            "Build: Done\nAll tests passed"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+const APP_DIR_NAME: &str = "claude-code-notification";
+
+/// Resolves an XDG base directory, falling back to `$HOME/<fallback>`.
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match std::env::var_os(var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(fallback),
+    };
+
+    Some(base.join(APP_DIR_NAME))
+}
+
+/// Directory holding the config file, honoring `XDG_CONFIG_HOME`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Directory for regenerable cached data, honoring `XDG_CACHE_HOME`.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Directory for persistent state such as logs, honoring `XDG_STATE_HOME`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
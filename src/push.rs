@@ -0,0 +1,261 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Public ntfy server used when the topic isn't a full URL.
+pub const NTFY_SERVER: &str = "https://ntfy.sh";
+
+/// Pushover's message endpoint.
+pub const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// How long a push or webhook request may take, since the hook waits on it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A JSON POST to a push service, built separately from sending it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushRequest {
+    pub url: String,
+    /// Headers beyond the JSON content type.
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+/// A mobile push service selected with `--push`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushProvider {
+    Ntfy,
+    Pushover,
+}
+
+impl PushProvider {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ntfy" => Some(PushProvider::Ntfy),
+            "pushover" => Some(PushProvider::Pushover),
+            _ => None,
+        }
+    }
+}
+
+/// Topics and tokens for push services (the `[push]` table).
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PushCredentials {
+    /// ntfy topic name, or a full URL for a self-hosted server.
+    pub ntfy_topic: Option<String>,
+    /// Access token for protected ntfy topics.
+    pub ntfy_token: Option<String>,
+    /// Pushover application token.
+    pub pushover_token: Option<String>,
+    /// Pushover user or group key.
+    pub pushover_user: Option<String>,
+}
+
+impl PushCredentials {
+    /// Reads credentials from `CLAUDE_NOTIFICATION_*` environment variables.
+    pub fn from_env<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        let var = |key: &str| lookup(key).filter(|v| !v.is_empty());
+        PushCredentials {
+            ntfy_topic: var("CLAUDE_NOTIFICATION_NTFY_TOPIC"),
+            ntfy_token: var("CLAUDE_NOTIFICATION_NTFY_TOKEN"),
+            pushover_token: var("CLAUDE_NOTIFICATION_PUSHOVER_TOKEN"),
+            pushover_user: var("CLAUDE_NOTIFICATION_PUSHOVER_USER"),
+        }
+    }
+
+    /// Layers `over` on top of these credentials, field by field.
+    pub fn merge(&self, over: &PushCredentials) -> PushCredentials {
+        PushCredentials {
+            ntfy_topic: over.ntfy_topic.clone().or_else(|| self.ntfy_topic.clone()),
+            ntfy_token: over.ntfy_token.clone().or_else(|| self.ntfy_token.clone()),
+            pushover_token: over
+                .pushover_token
+                .clone()
+                .or_else(|| self.pushover_token.clone()),
+            pushover_user: over
+                .pushover_user
+                .clone()
+                .or_else(|| self.pushover_user.clone()),
+        }
+    }
+}
+
+/// A push provider together with the credentials it needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushTarget {
+    Ntfy {
+        topic: String,
+        token: Option<String>,
+    },
+    Pushover {
+        token: String,
+        user: String,
+    },
+}
+
+impl PushTarget {
+    /// Pairs `provider` with its credentials, failing if any are missing.
+    pub fn new(provider: PushProvider, credentials: &PushCredentials) -> Result<Self> {
+        let missing = |key: &str, var: &str| {
+            anyhow::anyhow!(
+                "--push needs `{}` in the [push] config table or {}",
+                key,
+                var
+            )
+        };
+
+        match provider {
+            PushProvider::Ntfy => Ok(PushTarget::Ntfy {
+                topic: credentials
+                    .ntfy_topic
+                    .clone()
+                    .ok_or_else(|| missing("ntfy_topic", "CLAUDE_NOTIFICATION_NTFY_TOPIC"))?,
+                token: credentials.ntfy_token.clone(),
+            }),
+            PushProvider::Pushover => Ok(PushTarget::Pushover {
+                token: credentials.pushover_token.clone().ok_or_else(|| {
+                    missing("pushover_token", "CLAUDE_NOTIFICATION_PUSHOVER_TOKEN")
+                })?,
+                user: credentials
+                    .pushover_user
+                    .clone()
+                    .ok_or_else(|| missing("pushover_user", "CLAUDE_NOTIFICATION_PUSHOVER_USER"))?,
+            }),
+        }
+    }
+
+    pub fn request(&self, title: &str, msg: &str) -> PushRequest {
+        match self {
+            PushTarget::Ntfy { topic, token } => {
+                build_ntfy_request(topic, token.as_deref(), title, msg)
+            }
+            PushTarget::Pushover { token, user } => build_pushover_request(token, user, title, msg),
+        }
+    }
+}
+
+/// Builds an ntfy publish request. A topic given as a URL is split into its
+/// server and topic name so self-hosted servers work.
+pub fn build_ntfy_request(topic: &str, token: Option<&str>, title: &str, msg: &str) -> PushRequest {
+    let (server, topic) = match topic.rsplit_once('/') {
+        Some((server, name)) if server.contains("://") => (server, name),
+        _ => (NTFY_SERVER, topic),
+    };
+
+    PushRequest {
+        url: server.to_string(),
+        headers: token
+            .map(|token| ("Authorization".to_string(), format!("Bearer {}", token)))
+            .into_iter()
+            .collect(),
+        body: json!({
+            "topic": topic,
+            "title": title,
+            "message": msg,
+        }),
+    }
+}
+
+/// Builds a Pushover message request.
+pub fn build_pushover_request(token: &str, user: &str, title: &str, msg: &str) -> PushRequest {
+    PushRequest {
+        url: PUSHOVER_URL.to_string(),
+        headers: Vec::new(),
+        body: json!({
+            "token": token,
+            "user": user,
+            "title": title,
+            "message": msg,
+        }),
+    }
+}
+
+/// Sends a push request, failing on non-2xx responses or after
+/// [`REQUEST_TIMEOUT`].
+pub fn send_request(request: &PushRequest) -> Result<()> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?
+        .post(&request.url)
+        .json(&request.body);
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    builder.send()?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_ntfy_request() {
+        let request = build_ntfy_request("claude-alerts", None, "Claude Code", "Build finished");
+
+        assert_eq!(request.url, "https://ntfy.sh");
+        assert!(request.headers.is_empty());
+        assert_eq!(
+            request.body,
+            json!({"topic": "claude-alerts", "title": "Claude Code", "message": "Build finished"})
+        );
+    }
+
+    #[test]
+    fn test_build_ntfy_request_self_hosted_with_token() {
+        let request = build_ntfy_request(
+            "https://ntfy.example.com/claude",
+            Some("tk_secret"),
+            "Claude Code",
+            "Build finished",
+        );
+
+        assert_eq!(request.url, "https://ntfy.example.com");
+        assert_eq!(request.body["topic"], "claude");
+        assert_eq!(
+            request.headers,
+            vec![("Authorization".to_string(), "Bearer tk_secret".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_build_pushover_request() {
+        let request = build_pushover_request("app-token", "user-key", "Claude Code", "Done");
+
+        assert_eq!(request.url, PUSHOVER_URL);
+        assert!(request.headers.is_empty());
+        assert_eq!(
+            request.body,
+            json!({"token": "app-token", "user": "user-key", "title": "Claude Code", "message": "Done"})
+        );
+    }
+
+    #[test]
+    fn test_push_target_requires_credentials() {
+        let credentials = PushCredentials {
+            pushover_token: Some("app-token".to_string()),
+            ..Default::default()
+        };
+
+        let err = PushTarget::new(PushProvider::Pushover, &credentials).unwrap_err();
+        assert!(err.to_string().contains("pushover_user"));
+        assert!(PushTarget::new(PushProvider::Ntfy, &credentials).is_err());
+    }
+
+    #[test]
+    fn test_credentials_env_overrides_file() {
+        let file = PushCredentials {
+            ntfy_topic: Some("file-topic".to_string()),
+            ntfy_token: Some("file-token".to_string()),
+            ..Default::default()
+        };
+        let env = PushCredentials::from_env(|key| {
+            (key == "CLAUDE_NOTIFICATION_NTFY_TOPIC").then(|| "env-topic".to_string())
+        });
+
+        let merged = file.merge(&env);
+        assert_eq!(merged.ntfy_topic.as_deref(), Some("env-topic"));
+        assert_eq!(merged.ntfy_token.as_deref(), Some("file-token"));
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Deserializer};
+
+/// What happens to notifications during quiet hours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuietMode {
+    /// Show the notification without playing a sound.
+    #[default]
+    Silent,
+    /// Don't show the notification at all.
+    Suppress,
+}
+
+impl QuietMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "silent" => Some(QuietMode::Silent),
+            "suppress" => Some(QuietMode::Suppress),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            QuietMode::Silent => "silent",
+            QuietMode::Suppress => "suppress",
+        }
+    }
+}
+
+/// A daily local-time window from `quiet_start` to `quiet_end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub mode: QuietMode,
+}
+
+impl QuietHours {
+    pub fn contains(&self, now: NaiveTime) -> bool {
+        in_quiet_hours(now, self.start, self.end)
+    }
+}
+
+/// Returns true if `now` falls in `[start, end)`. A window whose end is
+/// before its start crosses midnight; an empty window (`start == end`)
+/// never matches.
+pub fn in_quiet_hours(now: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Parses a `HH:MM` time of day.
+pub fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+/// Deserializes an optional `HH:MM` string for the config file.
+pub(crate) fn deserialize_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveTime>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_time(&s)
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid time {:?}, expected HH:MM", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> NaiveTime {
+        parse_time(s).unwrap()
+    }
+
+    #[test]
+    fn test_in_quiet_hours_same_day() {
+        assert!(in_quiet_hours(t("13:00"), t("12:00"), t("14:00")));
+        assert!(in_quiet_hours(t("12:00"), t("12:00"), t("14:00")));
+        assert!(!in_quiet_hours(t("14:00"), t("12:00"), t("14:00")));
+    }
+
+    #[test]
+    fn test_in_quiet_hours_crosses_midnight() {
+        assert!(in_quiet_hours(t("23:30"), t("22:00"), t("07:00")));
+        assert!(in_quiet_hours(t("00:00"), t("22:00"), t("07:00")));
+        assert!(in_quiet_hours(t("06:59"), t("22:00"), t("07:00")));
+    }
+
+    #[test]
+    fn test_outside_quiet_hours() {
+        assert!(!in_quiet_hours(t("08:00"), t("22:00"), t("07:00")));
+        assert!(!in_quiet_hours(t("21:59"), t("22:00"), t("07:00")));
+        assert!(!in_quiet_hours(t("09:00"), t("12:00"), t("14:00")));
+        assert!(!in_quiet_hours(t("09:00"), t("09:00"), t("09:00")));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("07:30"), NaiveTime::from_hms_opt(7, 30, 0));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("7pm"), None);
+    }
+}
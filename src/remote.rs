@@ -0,0 +1,157 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::stable_hash;
+
+/// How long downloading a remote sound may take, since the hook waits on it.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns true when the sound name refers to a remote `http(s)://` URL.
+pub fn is_remote_sound(name: &str) -> bool {
+    name.starts_with("http://") || name.starts_with("https://")
+}
+
+/// Computes where a downloaded copy of `url` is cached. The file name is
+/// keyed on a hash of the URL and keeps the URL's extension so `afplay` can
+/// detect the format.
+pub fn cached_sound_path(url: &str, cache_dir: &Path) -> PathBuf {
+    let hash = format!("{:016x}", stable_hash(url.as_bytes()));
+
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|segment| segment.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.len() <= 4)
+        .filter(|ext| ext.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    match extension {
+        Some(ext) => cache_dir.join(format!("{}.{}", hash, ext)),
+        None => cache_dir.join(hash),
+    }
+}
+
+/// Resolves a remote sound to a local file, downloading it into `cache_dir`
+/// on first use.
+pub fn resolve_remote_sound(url: &str, cache_dir: &Path) -> Result<PathBuf> {
+    resolve_remote_sound_with(url, cache_dir, download)
+}
+
+fn resolve_remote_sound_with<F>(url: &str, cache_dir: &Path, download: F) -> Result<PathBuf>
+where
+    F: FnOnce(&str, &Path) -> Result<()>,
+{
+    let path = cached_sound_path(url, cache_dir);
+    if path.exists() {
+        return Ok(path);
+    }
+
+    fs::create_dir_all(cache_dir)?;
+
+    // Download to a temporary name first so a failed transfer never leaves a
+    // truncated file that would be treated as a cache hit.
+    let partial = path.with_extension("partial");
+    if let Err(e) = download(url, &partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &path)?;
+
+    Ok(path)
+}
+
+fn download(url: &str, dest: &Path) -> Result<()> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()?
+        .get(url)
+        .send()?
+        .error_for_status()?;
+    fs::write(dest, response.bytes()?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_remote_sound() {
+        assert!(is_remote_sound("https://example.com/chime.wav"));
+        assert!(is_remote_sound("http://intranet/chime.aiff"));
+        assert!(!is_remote_sound("Glass"));
+        assert!(!is_remote_sound("./sounds/chime.wav"));
+    }
+
+    #[test]
+    fn test_cached_sound_path() {
+        let cache_dir = Path::new("/cache");
+        let path = cached_sound_path("https://example.com/chime.wav?v=2", cache_dir);
+
+        assert_eq!(path.parent(), Some(cache_dir));
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("wav"));
+        assert_eq!(
+            path,
+            cached_sound_path("https://example.com/chime.wav?v=2", cache_dir)
+        );
+        assert_ne!(
+            path,
+            cached_sound_path("https://example.com/chime.wav?v=3", cache_dir)
+        );
+        assert_eq!(
+            cached_sound_path("https://example.com/sounds/", cache_dir).extension(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_remote_sound_cache_miss() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let url = "https://example.com/chime.wav";
+        let calls = Cell::new(0);
+
+        let path = resolve_remote_sound_with(url, temp_dir.path(), |_, dest| {
+            calls.set(calls.get() + 1);
+            fs::write(dest, b"RIFF")?;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(path, cached_sound_path(url, temp_dir.path()));
+        assert_eq!(fs::read(&path).unwrap(), b"RIFF");
+    }
+
+    #[test]
+    fn test_resolve_remote_sound_cache_hit() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let url = "https://example.com/chime.wav";
+        fs::write(cached_sound_path(url, temp_dir.path()), b"cached").unwrap();
+
+        let path = resolve_remote_sound_with(url, temp_dir.path(), |_, _| {
+            panic!("Download should not happen on a cache hit")
+        })
+        .unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"cached");
+    }
+
+    #[test]
+    fn test_resolve_remote_sound_download_failure() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let url = "https://example.com/chime.wav";
+
+        let result = resolve_remote_sound_with(url, temp_dir.path(), |_, dest| {
+            fs::write(dest, b"partial")?;
+            anyhow::bail!("connection reset")
+        });
+
+        assert!(result.is_err());
+        assert!(!cached_sound_path(url, temp_dir.path()).exists());
+    }
+}
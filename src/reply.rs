@@ -0,0 +1,95 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone};
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Appends a reply typed into a notification to `path` as one line with the
+/// local time and session id. Empty replies are ignored.
+pub fn record_reply(path: &Path, session_id: &str, text: &str) -> Result<()> {
+    record_reply_at(path, &Local::now(), session_id, text)
+}
+
+fn record_reply_at<Tz: TimeZone>(
+    path: &Path,
+    now: &DateTime<Tz>,
+    session_id: &str,
+    text: &str,
+) -> Result<()>
+where
+    Tz::Offset: Display,
+{
+    let Some(line) = reply_line(now, session_id, text) else {
+        return Ok(());
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Formats a reply as `YYYY-MM-DD HH:MM:SS [session] text`, joining
+/// multi-line replies so each stays on one line.
+fn reply_line<Tz: TimeZone>(now: &DateTime<Tz>, session_id: &str, text: &str) -> Option<String>
+where
+    Tz::Offset: Display,
+{
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{} [{}] {}",
+        now.format("%Y-%m-%d %H:%M:%S"),
+        session_id,
+        text
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 9, 14, 5, 30).unwrap()
+    }
+
+    #[test]
+    fn test_reply_line_format() {
+        assert_eq!(
+            reply_line(&at(), "abc123", "Check the flaky test").unwrap(),
+            "2024-03-09 14:05:30 [abc123] Check the flaky test"
+        );
+    }
+
+    #[test]
+    fn test_reply_line_joins_lines() {
+        assert_eq!(
+            reply_line(&at(), "abc123", "  first\nsecond\t ").unwrap(),
+            "2024-03-09 14:05:30 [abc123] first second"
+        );
+        assert_eq!(reply_line(&at(), "abc123", " \n "), None);
+    }
+
+    #[test]
+    fn test_record_reply_appends() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("notes").join("replies.txt");
+
+        record_reply_at(&path, &at(), "abc123", "first").unwrap();
+        record_reply_at(&path, &at(), "def456", "").unwrap();
+        record_reply_at(&path, &at(), "def456", "second").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "2024-03-09 14:05:30 [abc123] first\n2024-03-09 14:05:30 [def456] second\n"
+        );
+    }
+}
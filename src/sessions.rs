@@ -0,0 +1,128 @@
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// Session id patterns mapped to sound names (the `[sessions]` table).
+/// Entries keep their order from the config file so the first matching
+/// pattern wins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionSounds(pub Vec<(String, String)>);
+
+impl SessionSounds {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the sound of the first pattern matching `session_id`.
+    pub fn sound_for(&self, session_id: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(pattern, _)| match_session_pattern(pattern, session_id))
+            .map(|(_, sound)| sound.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionSounds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedVisitor;
+
+        impl<'de> Visitor<'de> for OrderedVisitor {
+            type Value = SessionSounds;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a table of session id patterns to sound names")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(SessionSounds(entries))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedVisitor)
+    }
+}
+
+/// Matches `session_id` against a glob where `*` matches any run of
+/// characters and `?` matches exactly one. Everything else is literal.
+pub fn match_session_pattern(pattern: &str, session_id: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = session_id.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text index it is currently covering
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` absorb one more character and retry
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_session_pattern_star() {
+        assert!(match_session_pattern("feat-*", "feat-login"));
+        assert!(match_session_pattern("feat-*", "feat-"));
+        assert!(match_session_pattern("*-review", "pr-42-review"));
+        assert!(match_session_pattern("a*b*c", "axxbyyc"));
+        assert!(match_session_pattern("*", ""));
+    }
+
+    #[test]
+    fn test_match_session_pattern_question_mark() {
+        assert!(match_session_pattern("run-?", "run-1"));
+        assert!(!match_session_pattern("run-?", "run-12"));
+        assert!(!match_session_pattern("run-?", "run-"));
+    }
+
+    #[test]
+    fn test_match_session_pattern_literal() {
+        assert!(match_session_pattern("abc123", "abc123"));
+        assert!(!match_session_pattern("abc123", "abc1234"));
+    }
+
+    #[test]
+    fn test_match_session_pattern_non_matching() {
+        assert!(!match_session_pattern("feat-*", "fix-login"));
+        assert!(!match_session_pattern("*-review", "pr-42"));
+        assert!(!match_session_pattern("", "abc"));
+    }
+
+    #[test]
+    fn test_sound_for_first_match_wins() {
+        let sessions: SessionSounds =
+            toml::from_str("\"feat-login\" = \"Hero\"\n\"feat-*\" = \"Ping\"\n\"*\" = \"Tink\"\n")
+                .unwrap();
+
+        assert_eq!(sessions.sound_for("feat-login"), Some("Hero"));
+        assert_eq!(sessions.sound_for("feat-search"), Some("Ping"));
+        assert_eq!(sessions.sound_for("other"), Some("Tink"));
+        assert_eq!(SessionSounds::default().sound_for("other"), None);
+    }
+}
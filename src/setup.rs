@@ -1,10 +1,19 @@
 use anyhow::Result;
-use inquire::{validator::Validation, Select, Text};
+use claude_code_notification::{
+    is_supported_audio, paths, play_sound, Playback, Sound, DEFAULT_TITLE, SYSTEM_SOUNDS_DIR,
+};
+use inquire::{validator::Validation, Confirm, Select, Text};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-const SYSTEM_SOUNDS_DIR: &str = "/System/Library/Sounds";
+use crate::style;
+
+const SOUNDS_CACHE_FILE: &str = "system-sounds.json";
+const SOUNDS_CACHE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
 const DEFAULT_SOUNDS: &[&str] = &[
     "Basso",
     "Blow",
@@ -27,7 +36,94 @@ fn get_claude_settings_path() -> Result<PathBuf> {
     Ok(PathBuf::from(home).join(".claude").join("settings.json"))
 }
 
+/// Picks the settings file to update: an explicit path, the project-local
+/// `.claude/settings.json` under `cwd`, or the user's global settings.
+pub fn resolve_settings_path(
+    explicit: Option<&Path>,
+    project: bool,
+    cwd: &Path,
+) -> Result<PathBuf> {
+    match explicit {
+        Some(path) if path.is_absolute() => Ok(path.to_path_buf()),
+        Some(path) => Ok(cwd.join(path)),
+        None if project => Ok(cwd.join(".claude").join("settings.json")),
+        None => get_claude_settings_path(),
+    }
+}
+
+/// Creates the settings file's directory if needed and checks that it can
+/// be written to.
+fn ensure_writable_parent(settings_path: &Path) -> Result<()> {
+    let Some(parent) = settings_path.parent() else {
+        return Ok(());
+    };
+
+    fs::create_dir_all(parent)?;
+    if fs::metadata(parent)?.permissions().readonly() {
+        anyhow::bail!("Settings directory is not writable: {}", parent.display());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CachedSounds {
+    /// Seconds since the Unix epoch when the list was generated.
+    timestamp: u64,
+    sounds: Vec<String>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns the cached sound list if the cache exists and is younger than
+/// `max_age`.
+fn load_cached_sounds(cache: &Path, max_age: Duration) -> Option<Vec<String>> {
+    let content = fs::read_to_string(cache).ok()?;
+    let cached: CachedSounds = serde_json::from_str(&content).ok()?;
+
+    let age = unix_now().saturating_sub(cached.timestamp);
+    if age < max_age.as_secs() {
+        Some(cached.sounds)
+    } else {
+        None
+    }
+}
+
+fn store_cached_sounds(cache: &Path, sounds: &[String]) -> Result<()> {
+    if let Some(parent) = cache.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let cached = CachedSounds {
+        timestamp: unix_now(),
+        sounds: sounds.to_vec(),
+    };
+    fs::write(cache, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
 fn get_available_system_sounds() -> Vec<String> {
+    let cache = paths::cache_dir().map(|dir| dir.join(SOUNDS_CACHE_FILE));
+    if let Some(sounds) = cache
+        .as_deref()
+        .and_then(|cache| load_cached_sounds(cache, SOUNDS_CACHE_MAX_AGE))
+    {
+        return sounds;
+    }
+
+    let sounds = scan_system_sounds();
+    if let Some(cache) = &cache {
+        // The cache is only an optimization, so failing to write it is fine
+        let _ = store_cached_sounds(cache, &sounds);
+    }
+    sounds
+}
+
+fn scan_system_sounds() -> Vec<String> {
     let system_sounds_path = Path::new(SYSTEM_SOUNDS_DIR);
     if !system_sounds_path.exists() {
         return DEFAULT_SOUNDS.iter().map(|s| s.to_string()).collect();
@@ -73,11 +169,205 @@ fn validate_sound_path(
     }
 }
 
-pub fn run_setup() -> Result<()> {
-    println!("🔧 Setting up Claude Code notifications\n");
+/// Quotes an argument for the shell that runs the hook command. Arguments
+/// made only of safe characters are left bare; anything else is wrapped in
+/// double quotes with `"`, `\\`, `$`, and backticks escaped.
+fn shell_quote(s: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    if !s.is_empty() && s.chars().all(is_safe) {
+        return s.to_string();
+    }
+
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Builds the hook command that runs the notifier with the chosen options.
+fn build_command_string(sound: &str, title: Option<&str>) -> String {
+    let mut command = format!("claude-code-notification --sound {}", shell_quote(sound));
+    if let Some(title) = title {
+        command.push_str(&format!(" --title {}", shell_quote(title)));
+    }
+    command
+}
+
+/// Returns the path the existing settings are backed up to.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Loads the existing settings. If they can't be parsed, they are copied to
+/// `<path>.bak` before an empty object is returned so the user's file isn't
+/// silently lost when it's overwritten.
+fn load_or_backup_settings(path: &Path) -> Result<Value> {
+    if !path.exists() {
+        return Ok(json!({}));
+    }
+
+    let content = fs::read_to_string(path)?;
+    match serde_json::from_str(&content) {
+        Ok(settings) => Ok(settings),
+        Err(e) => {
+            let backup = backup_path(path);
+            fs::copy(path, &backup)?;
+            println!(
+                "{}",
+                style::err(&format!(
+                    "⚠️  Existing settings could not be parsed ({}); backed up to {}",
+                    e,
+                    backup.display()
+                ))
+            );
+            Ok(json!({}))
+        }
+    }
+}
 
-    let available_sounds = get_available_system_sounds();
-    let mut sound_options: Vec<String> = available_sounds;
+/// Collapses whitespace so commands that differ only in spacing compare
+/// equal.
+fn normalize_command(command: &str) -> String {
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Builds the `hooks` entry that runs `command` for `event` on tools
+/// matching `matcher`, as used by `PreToolUse` and `PostToolUse`.
+pub fn build_matcher_hook(event: &str, matcher: &str, command: &str) -> Value {
+    json!({
+        event: [
+            {
+                "matcher": matcher,
+                "hooks": [
+                    {
+                        "type": "command",
+                        "command": command
+                    }
+                ]
+            }
+        ]
+    })
+}
+
+/// Adds a `Notification` command hook to `settings`, keeping any existing
+/// hooks.
+fn add_notification_hook(settings: &mut Value, command: &str) -> Result<()> {
+    add_hooks(
+        settings,
+        &json!({
+            "Notification": [
+                {
+                    "hooks": [
+                        {
+                            "type": "command",
+                            "command": command
+                        }
+                    ]
+                }
+            ]
+        }),
+    )
+}
+
+/// Appends each event's hook groups in `new_hooks` to `settings`, keeping
+/// any existing hooks. Within an event, hooks with the same matcher and
+/// normalized command are collapsed to the first occurrence, so running
+/// setup repeatedly leaves a single entry.
+fn add_hooks(settings: &mut Value, new_hooks: &Value) -> Result<()> {
+    if settings.is_null() {
+        *settings = json!({});
+    }
+    let Some(root) = settings.as_object_mut() else {
+        anyhow::bail!("Settings file must contain a JSON object");
+    };
+
+    let hooks = root.entry("hooks").or_insert_with(|| json!({}));
+    let Some(hooks) = hooks.as_object_mut() else {
+        anyhow::bail!("\"hooks\" in the settings file must be an object");
+    };
+
+    for (event, new_groups) in new_hooks.as_object().into_iter().flatten() {
+        let groups = hooks.entry(event.as_str()).or_insert_with(|| json!([]));
+        let Some(groups) = groups.as_array_mut() else {
+            anyhow::bail!("\"hooks.{}\" in the settings file must be an array", event);
+        };
+        groups.extend(new_groups.as_array().into_iter().flatten().cloned());
+
+        let mut seen = HashSet::new();
+        groups.retain_mut(|group| {
+            let matcher = group
+                .get("matcher")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let Some(entries) = group.get_mut("hooks").and_then(Value::as_array_mut) else {
+                return true;
+            };
+            entries.retain(|entry| match entry.get("command").and_then(Value::as_str) {
+                Some(command) => seen.insert((matcher.clone(), normalize_command(command))),
+                None => true,
+            });
+            !entries.is_empty()
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether `settings` has any `Notification` command hook.
+fn has_notification_hook(settings: &Value) -> bool {
+    settings
+        .pointer("/hooks/Notification")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.get("hooks").and_then(Value::as_array))
+        .flatten()
+        .any(|entry| entry.get("command").and_then(Value::as_str).is_some())
+}
+
+/// Whether to suggest running `setup`: only in interactive use, and only
+/// when the settings file has no notification hook. A missing or
+/// unreadable file counts as having none.
+pub fn needs_setup_hint(settings_path: &Path, interactive: bool) -> bool {
+    if !interactive {
+        return false;
+    }
+
+    let settings = fs::read_to_string(settings_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+    !has_notification_hook(&settings)
+}
+
+/// Repeats `choose` until `keep` accepts the choice, previewing each one
+/// in between.
+fn choose_with_preview<C, P, K>(mut choose: C, mut preview: P, mut keep: K) -> Result<String>
+where
+    C: FnMut() -> Result<String>,
+    P: FnMut(&str),
+    K: FnMut() -> Result<bool>,
+{
+    loop {
+        let choice = choose()?;
+        preview(&choice);
+        if keep()? {
+            return Ok(choice);
+        }
+    }
+}
+
+fn prompt_sound() -> Result<String> {
+    let mut sound_options = get_available_system_sounds();
     sound_options.push("Custom file path...".to_string());
 
     let sound_choice = Select::new("Select a notification sound:", sound_options)
@@ -86,57 +376,95 @@ pub fn run_setup() -> Result<()> {
         )
         .prompt()?;
 
-    let selected_sound = if sound_choice == "Custom file path..." {
-        Text::new("Enter the path to your custom sound file:")
-            .with_help_message("Supported formats: .wav, .aiff, .mp3, .m4a")
+    if sound_choice == "Custom file path..." {
+        let path = Text::new("Enter the path to your custom sound file:")
+            .with_help_message("Supported formats: .wav, .aiff, .mp3, .m4a, .caf")
             .with_validator(validate_sound_path)
-            .prompt()?
+            .prompt()?;
+        if !is_supported_audio(&path) {
+            println!(
+                "{}",
+                style::err("⚠️  afplay may not support this file type; the sound might not play")
+            );
+        }
+        Ok(path)
     } else {
-        sound_choice
-    };
-
-    let settings_path = get_claude_settings_path()?;
+        Ok(sound_choice)
+    }
+}
 
-    // Create .claude directory if it doesn't exist
-    if let Some(parent) = settings_path.parent() {
-        fs::create_dir_all(parent)?;
+fn preview_sound(name: &str) {
+    let sound = Sound::from_name(name);
+    if let Err(e) = play_sound(&sound, &Playback::default()) {
+        println!(
+            "{}",
+            style::err(&format!("⚠️  Could not preview sound: {}", e))
+        );
     }
+}
+
+/// Runs the interactive setup. With `tool_hook`, an `(event, matcher)`
+/// pair, the command is registered for that tool event instead of
+/// `Notification`.
+pub fn run_setup(settings_path: &Path, tool_hook: Option<(&str, &str)>) -> Result<()> {
+    println!("🔧 Setting up Claude Code notifications\n");
+
+    // Fail before prompting if the settings can't be written
+    ensure_writable_parent(settings_path)?;
+
+    let selected_sound = choose_with_preview(prompt_sound, preview_sound, || {
+        Ok(Confirm::new("Keep this sound?")
+            .with_default(true)
+            .with_help_message("Answer no to pick another")
+            .prompt()?)
+    })?;
+
+    let title = Text::new("Notification title:")
+        .with_initial_value(DEFAULT_TITLE)
+        .with_help_message("Leave empty to use the title provided by Claude Code")
+        .prompt()?;
+    let title = title.trim();
+    let title = (!title.is_empty()).then_some(title);
 
     // Read existing settings or create new ones
-    let mut settings: Value = if settings_path.exists() {
-        let content = fs::read_to_string(&settings_path)?;
-        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
-    } else {
-        json!({})
-    };
+    let mut settings = load_or_backup_settings(settings_path)?;
 
     // Update the hooks configuration
-    let notification_command = if selected_sound.contains('/') {
-        format!("claude-code-notification --sound \"{}\"", selected_sound)
-    } else {
-        format!("claude-code-notification --sound {}", selected_sound)
-    };
-
-    settings["hooks"] = json!({
-        "Notification": [
-            {
-                "hooks": [
-                    {
-                        "type": "command",
-                        "command": notification_command
-                    }
-                ]
-            }
-        ]
-    });
+    let notification_command = build_command_string(&selected_sound, title);
+
+    match tool_hook {
+        Some((event, matcher)) => add_hooks(
+            &mut settings,
+            &build_matcher_hook(event, matcher, &notification_command),
+        )?,
+        None => add_notification_hook(&mut settings, &notification_command)?,
+    }
 
     // Write updated settings
     let settings_json = serde_json::to_string_pretty(&settings)?;
-    fs::write(&settings_path, settings_json)?;
+    if let Err(e) = fs::write(settings_path, settings_json) {
+        println!(
+            "{}",
+            style::err(&format!(
+                "❌ Failed to write settings file: {}",
+                settings_path.display()
+            ))
+        );
+        return Err(e.into());
+    }
 
-    println!("✅ Claude Code settings updated successfully!");
+    println!(
+        "{}",
+        style::ok("✅ Claude Code settings updated successfully!")
+    );
     println!("📁 Settings file: {}", settings_path.display());
     println!("🔊 Selected sound: {}", selected_sound);
+    if let Some(title) = title {
+        println!("🏷️  Notification title: {}", title);
+    }
+    if let Some((event, matcher)) = tool_hook {
+        println!("🪝 Hook: {} (matcher: {})", event, matcher);
+    }
     println!("\nYour Claude Code notifications are now configured.");
 
     Ok(())
@@ -302,4 +630,278 @@ mod tests {
         let sound_names: std::collections::HashSet<_> = sounds.iter().collect();
         assert!(sound_names.contains(&"Glass".to_string()));
     }
+
+    #[test]
+    fn test_load_cached_sounds_fresh_hit() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let cache = temp_dir.path().join("cache").join(SOUNDS_CACHE_FILE);
+        let sounds = vec!["Glass".to_string(), "Hero".to_string()];
+
+        store_cached_sounds(&cache, &sounds).expect("Failed to write sound cache");
+
+        assert_eq!(
+            load_cached_sounds(&cache, SOUNDS_CACHE_MAX_AGE),
+            Some(sounds)
+        );
+    }
+
+    #[test]
+    fn test_load_cached_sounds_stale_miss() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let cache = temp_dir.path().join(SOUNDS_CACHE_FILE);
+        let stale = CachedSounds {
+            timestamp: unix_now() - 2 * 60 * 60,
+            sounds: vec!["Glass".to_string()],
+        };
+        std::fs::write(&cache, serde_json::to_string(&stale).unwrap()).unwrap();
+
+        assert_eq!(load_cached_sounds(&cache, SOUNDS_CACHE_MAX_AGE), None);
+    }
+
+    #[test]
+    fn test_load_cached_sounds_missing_cache() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let cache = temp_dir.path().join(SOUNDS_CACHE_FILE);
+
+        assert_eq!(load_cached_sounds(&cache, SOUNDS_CACHE_MAX_AGE), None);
+    }
+
+    #[test]
+    fn test_build_command_string_without_title() {
+        assert_eq!(
+            build_command_string("Glass", None),
+            "claude-code-notification --sound Glass"
+        );
+        assert_eq!(
+            build_command_string("/Users/dev/sounds/alert.wav", None),
+            "claude-code-notification --sound \"/Users/dev/sounds/alert.wav\""
+        );
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("Glass"), "Glass");
+        assert_eq!(shell_quote(""), "\"\"");
+        assert_eq!(
+            shell_quote("/Users/dev/My Sounds/a.wav"),
+            "\"/Users/dev/My Sounds/a.wav\""
+        );
+        assert_eq!(
+            shell_quote(r#"/tmp/say "hi".wav"#),
+            r#""/tmp/say \"hi\".wav""#
+        );
+        assert_eq!(shell_quote(r"C:\sounds\a.wav"), r#""C:\\sounds\\a.wav""#);
+        assert_eq!(shell_quote("$HOME/`x`"), r#""\$HOME/\`x\`""#);
+    }
+
+    #[test]
+    fn test_shell_quote_round_trips() {
+        for arg in [r#"/tmp/say "hi".wav"#, r"back\slash", "two words"] {
+            assert_eq!(
+                claude_code_notification::split_player_args(&shell_quote(arg)),
+                vec![arg]
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_command_string_with_title() {
+        assert_eq!(
+            build_command_string("Glass", Some("Claude Code")),
+            "claude-code-notification --sound Glass --title \"Claude Code\""
+        );
+        assert_eq!(
+            build_command_string("Hero", Some("Work")),
+            "claude-code-notification --sound Hero --title Work"
+        );
+    }
+
+    #[test]
+    fn test_resolve_settings_path_explicit() {
+        let cwd = Path::new("/work/repo");
+
+        assert_eq!(
+            resolve_settings_path(Some(Path::new("/etc/claude/settings.json")), false, cwd)
+                .unwrap(),
+            PathBuf::from("/etc/claude/settings.json")
+        );
+        assert_eq!(
+            resolve_settings_path(Some(Path::new("custom.json")), false, cwd).unwrap(),
+            PathBuf::from("/work/repo/custom.json")
+        );
+    }
+
+    #[test]
+    fn test_resolve_settings_path_project() {
+        let cwd = Path::new("/work/repo");
+
+        assert_eq!(
+            resolve_settings_path(None, true, cwd).unwrap(),
+            PathBuf::from("/work/repo/.claude/settings.json")
+        );
+    }
+
+    #[test]
+    fn test_ensure_writable_parent_creates_directory() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let settings_path = temp_dir.path().join(".claude").join("settings.json");
+
+        ensure_writable_parent(&settings_path).expect("Directory should be writable");
+        assert!(settings_path.parent().unwrap().is_dir());
+    }
+
+    #[test]
+    fn test_load_or_backup_settings_valid() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let settings_path = temp_dir.path().join("settings.json");
+        std::fs::write(&settings_path, r#"{"model": "opus"}"#).unwrap();
+
+        let settings = load_or_backup_settings(&settings_path).unwrap();
+        assert_eq!(settings["model"], "opus");
+        assert!(!backup_path(&settings_path).exists());
+    }
+
+    #[test]
+    fn test_load_or_backup_settings_invalid() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let settings_path = temp_dir.path().join("settings.json");
+        let broken = r#"{"model": "opus",}"#;
+        std::fs::write(&settings_path, broken).unwrap();
+
+        let settings = load_or_backup_settings(&settings_path).unwrap();
+        assert_eq!(settings, json!({}));
+
+        let backup = temp_dir.path().join("settings.json.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), broken);
+    }
+
+    #[test]
+    fn test_load_or_backup_settings_missing() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let settings_path = temp_dir.path().join("settings.json");
+
+        assert_eq!(load_or_backup_settings(&settings_path).unwrap(), json!({}));
+        assert!(!backup_path(&settings_path).exists());
+    }
+
+    #[test]
+    fn test_choose_with_preview_keeps_first_choice() {
+        let mut previewed = Vec::new();
+        let choice = choose_with_preview(
+            || Ok("Glass".to_string()),
+            |s| previewed.push(s.to_string()),
+            || Ok(true),
+        )
+        .unwrap();
+
+        assert_eq!(choice, "Glass");
+        assert_eq!(previewed, vec!["Glass"]);
+    }
+
+    #[test]
+    fn test_choose_with_preview_reprompts_on_no() {
+        let mut choices = vec!["Hero", "Glass"].into_iter();
+        let mut answers = vec![false, true].into_iter();
+        let mut previewed = Vec::new();
+
+        let choice = choose_with_preview(
+            || Ok(choices.next().unwrap().to_string()),
+            |s| previewed.push(s.to_string()),
+            || Ok(answers.next().unwrap()),
+        )
+        .unwrap();
+
+        assert_eq!(choice, "Glass");
+        assert_eq!(previewed, vec!["Hero", "Glass"]);
+    }
+
+    #[test]
+    fn test_needs_setup_hint() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("settings.json");
+
+        // Missing settings
+        assert!(needs_setup_hint(&path, true));
+        assert!(!needs_setup_hint(&path, false));
+
+        // Settings with only other hooks
+        fs::write(
+            &path,
+            r#"{"hooks": {"Stop": [{"hooks": [{"type": "command", "command": "say done"}]}]}}"#,
+        )
+        .unwrap();
+        assert!(needs_setup_hint(&path, true));
+
+        // Settings with a notification hook
+        let mut settings = json!({});
+        add_notification_hook(&mut settings, "claude-code-notification").unwrap();
+        fs::write(&path, settings.to_string()).unwrap();
+        assert!(!needs_setup_hint(&path, true));
+        assert!(!needs_setup_hint(&path, false));
+    }
+
+    #[test]
+    fn test_build_matcher_hook() {
+        assert_eq!(
+            build_matcher_hook("PreToolUse", "Bash", "claude-code-notification"),
+            json!({
+                "PreToolUse": [
+                    {
+                        "matcher": "Bash",
+                        "hooks": [{"type": "command", "command": "claude-code-notification"}]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_matcher_hooks_dedupe_per_matcher() {
+        let mut settings = json!({});
+        add_notification_hook(&mut settings, "claude-code-notification").unwrap();
+        for matcher in ["Bash", "Bash", "Edit|Write"] {
+            let hook = build_matcher_hook("PostToolUse", matcher, "claude-code-notification");
+            add_hooks(&mut settings, &hook).unwrap();
+        }
+
+        let groups = settings["hooks"]["PostToolUse"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0]["matcher"], "Bash");
+        assert_eq!(groups[1]["matcher"], "Edit|Write");
+        assert_eq!(
+            settings["hooks"]["Notification"].as_array().unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_add_notification_hook_is_idempotent() {
+        let mut settings = json!({});
+        add_notification_hook(&mut settings, "claude-code-notification --sound Glass").unwrap();
+        add_notification_hook(&mut settings, "claude-code-notification  --sound Glass").unwrap();
+
+        let groups = settings["hooks"]["Notification"].as_array().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(
+            groups[0]["hooks"][0]["command"],
+            "claude-code-notification --sound Glass"
+        );
+    }
+
+    #[test]
+    fn test_add_notification_hook_keeps_other_hooks() {
+        let mut settings = json!({
+            "hooks": {
+                "Stop": [{"hooks": [{"type": "command", "command": "say done"}]}],
+                "Notification": [{"hooks": [{"type": "command", "command": "say input"}]}]
+            }
+        });
+        add_notification_hook(&mut settings, "claude-code-notification").unwrap();
+
+        assert_eq!(settings["hooks"]["Stop"].as_array().unwrap().len(), 1);
+        let groups = settings["hooks"]["Notification"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0]["hooks"][0]["command"], "say input");
+        assert_eq!(groups[1]["hooks"][0]["command"], "claude-code-notification");
+    }
 }
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::backend::{show_via_backend, Backend};
+use crate::output::{self, OutputFormat};
+use crate::{PreparedNotification, WarningSink};
+
+/// Displays prepared notifications. Sounds are played separately, so a
+/// sink only sees the notification itself.
+pub trait NotificationSink: fmt::Debug + Send + Sync {
+    fn show(&self, prepared: &PreparedNotification) -> Result<()>;
+}
+
+/// Shows notifications for real: in the terminal for the terminal-based
+/// formats, otherwise through the selected backend.
+#[derive(Debug, Clone, Default)]
+pub struct SystemSink {
+    pub format: OutputFormat,
+    pub backend: Backend,
+    /// Receives problems handling a click, such as a URL that won't open.
+    pub warnings: WarningSink,
+}
+
+impl NotificationSink for SystemSink {
+    fn show(&self, prepared: &PreparedNotification) -> Result<()> {
+        if output::emit(self.format, prepared)? {
+            return Ok(());
+        }
+        show_via_backend(self.backend, prepared, &self.warnings)
+    }
+}
+
+/// Records notifications instead of showing them, so tests can assert on
+/// what would have been displayed.
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    shown: Mutex<Vec<PreparedNotification>>,
+}
+
+impl RecordingSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every notification shown so far, oldest first.
+    pub fn shown(&self) -> Vec<PreparedNotification> {
+        self.shown.lock().unwrap().clone()
+    }
+}
+
+impl NotificationSink for RecordingSink {
+    fn show(&self, prepared: &PreparedNotification) -> Result<()> {
+        self.shown.lock().unwrap().push(prepared.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Notifier;
+
+    #[test]
+    fn test_recording_sink_records_in_order() {
+        let sink = RecordingSink::new();
+        let first = Notifier::new().title("Build").message("Started").prepare();
+        let second = Notifier::new().title("Build").message("Done").prepare();
+
+        sink.show(&first).unwrap();
+        sink.show(&second).unwrap();
+
+        assert_eq!(sink.shown(), vec![first, second]);
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{generate_notification_id, paths, NotificationInput};
+
+const SPOOL_DIR: &str = "spool";
+
+/// Directory holding notifications that couldn't be delivered.
+pub fn spool_dir() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(SPOOL_DIR))
+}
+
+/// Saves `input` to the spool for a later `flush`. Files are named by send
+/// time so they're retried in order.
+pub fn spool_input(dir: &Path, input: &NotificationInput) -> Result<PathBuf> {
+    let now = SystemTime::now();
+    let nanos = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let path = dir.join(format!(
+        "{:020}-{}.json",
+        nanos,
+        generate_notification_id(input, now)
+    ));
+
+    fs::create_dir_all(dir)?;
+    fs::write(&path, serde_json::to_string(input)?)
+        .with_context(|| format!("Failed to spool notification to {}", path.display()))?;
+    Ok(path)
+}
+
+/// Retries every spooled notification with `send`, removing the ones that
+/// are delivered and returning how many were. Entries that fail, or can't
+/// be read, stay in place for the next flush.
+pub fn flush_spool<F>(dir: &Path, mut send: F) -> Result<usize>
+where
+    F: FnMut(&NotificationInput) -> Result<()>,
+{
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    entries.sort();
+
+    let mut delivered = 0;
+    for path in entries {
+        let Ok(input) = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<NotificationInput>(&content)?))
+        else {
+            continue;
+        };
+
+        if send(&input).is_ok() {
+            fs::remove_file(&path)?;
+            delivered += 1;
+        }
+    }
+    Ok(delivered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn input(message: &str) -> NotificationInput {
+        NotificationInput {
+            session_id: "abc123".to_string(),
+            transcript_path: "/tmp/transcript.jsonl".to_string(),
+            message: message.to_string(),
+            title: None,
+            hook_event_name: None,
+            stop_hook_active: None,
+            timeout_ms: None,
+        }
+    }
+
+    fn spooled(dir: &Path) -> usize {
+        fs::read_dir(dir).map(|e| e.count()).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_spool_input_round_trips() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let dir = temp_dir.path().join("spool");
+
+        let path = spool_input(&dir, &input("Build finished")).unwrap();
+
+        let saved: NotificationInput =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(saved.message, "Build finished");
+        assert_eq!(saved.session_id, "abc123");
+    }
+
+    #[test]
+    fn test_flush_spool_delivers_in_order() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        spool_input(temp_dir.path(), &input("first")).unwrap();
+        spool_input(temp_dir.path(), &input("second")).unwrap();
+
+        let mut sent = Vec::new();
+        let delivered = flush_spool(temp_dir.path(), |input| {
+            sent.push(input.message.clone());
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(delivered, 2);
+        assert_eq!(sent, ["first", "second"]);
+        assert_eq!(spooled(temp_dir.path()), 0);
+    }
+
+    #[test]
+    fn test_flush_spool_keeps_failing_entries() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        spool_input(temp_dir.path(), &input("deliverable")).unwrap();
+        let stuck = spool_input(temp_dir.path(), &input("stuck")).unwrap();
+        fs::write(temp_dir.path().join("corrupt.json"), "{").unwrap();
+
+        let flush = || {
+            flush_spool(temp_dir.path(), |input| {
+                if input.message == "stuck" {
+                    anyhow::bail!("no notification server");
+                }
+                Ok(())
+            })
+            .unwrap()
+        };
+
+        assert_eq!(flush(), 1);
+        assert_eq!(flush(), 0);
+        assert!(stuck.exists());
+        assert_eq!(spooled(temp_dir.path()), 2);
+    }
+
+    #[test]
+    fn test_flush_missing_spool() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let delivered = flush_spool(&temp_dir.path().join("none"), |_| Ok(())).unwrap();
+
+        assert_eq!(delivered, 0);
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::Result;
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::paths;
+
+const SEEN_SESSIONS_FILE: &str = "seen-sessions";
+const DISABLED_FILE: &str = "disabled";
+const BADGE_FILE: &str = "badge-count";
+const LAST_NOTIFIED_FILE: &str = "last-notified";
+const SESSION_COUNTS_FILE: &str = "session-counts";
+
+/// Location of the file recording which sessions have been notified.
+pub fn seen_sessions_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(SEEN_SESSIONS_FILE))
+}
+
+/// Location of the marker file whose presence disables notifications.
+pub fn disabled_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(DISABLED_FILE))
+}
+
+/// Returns false once `disable` has created the marker file at `state`.
+pub fn notifications_enabled(state: &Path) -> bool {
+    !state.exists()
+}
+
+/// Enables notifications by removing the marker file at `state`, or
+/// disables them by creating it.
+pub fn set_enabled(state: &Path, on: bool) -> Result<()> {
+    if on {
+        match fs::remove_file(state) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => return Ok(()),
+        }
+    }
+
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(state, "")?;
+    Ok(())
+}
+
+/// Location of the file holding the badge counter.
+pub fn badge_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(BADGE_FILE))
+}
+
+/// Increments the counter in `state`, returning the new count, which
+/// `--count-badge` shows after the message text in place of a dock or
+/// taskbar badge. A missing or unreadable counter starts from zero.
+pub fn bump_badge(state: &Path) -> Result<u32> {
+    let count = fs::read_to_string(state)
+        .ok()
+        .and_then(|content| content.trim().parse::<u32>().ok())
+        .unwrap_or(0)
+        .saturating_add(1);
+
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(state, format!("{}\n", count))?;
+    Ok(count)
+}
+
+/// Resets the badge counter by removing `state`.
+pub fn clear_badge(state: &Path) -> Result<()> {
+    match fs::remove_file(state) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Location of the file holding each session's last notification time.
+pub fn last_notified_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(LAST_NOTIFIED_FILE))
+}
+
+/// Reads the per-session values in `state`, one `session\tvalue` line each.
+/// A missing file has none, and lines that don't parse are skipped.
+fn read_session_map<T: FromStr>(state: &Path) -> Result<Vec<(String, T)>> {
+    match fs::read_to_string(state) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|line| {
+                let (session, value) = line.rsplit_once('\t')?;
+                Some((session.to_string(), value.parse().ok()?))
+            })
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replaces the per-session values in `state` with `entries`.
+fn write_session_map<T: Display>(state: &Path, entries: &[(String, T)]) -> Result<()> {
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content: String = entries
+        .iter()
+        .map(|(session, value)| format!("{}\t{}\n", session, value))
+        .collect();
+    fs::write(state, content)?;
+    Ok(())
+}
+
+/// Records `now` as `session_id`'s last notification time in `state`,
+/// returning how long it has been since the previous one. The first
+/// notification of a session has none.
+pub fn since_last(state: &Path, session_id: &str, now: SystemTime) -> Result<Option<Duration>> {
+    let mut times: Vec<(String, u64)> = read_session_map(state)?;
+
+    let now_secs = now.duration_since(UNIX_EPOCH)?.as_secs();
+    let previous = match times.iter_mut().find(|(session, _)| session == session_id) {
+        Some((_, secs)) => Some(std::mem::replace(secs, now_secs)),
+        None => {
+            times.push((session_id.to_string(), now_secs));
+            None
+        }
+    };
+    write_session_map(state, &times)?;
+
+    Ok(previous.map(|secs| Duration::from_secs(now_secs.saturating_sub(secs))))
+}
+
+/// Location of the file counting each session's notifications.
+pub fn session_counts_path() -> Option<PathBuf> {
+    Some(paths::state_dir()?.join(SESSION_COUNTS_FILE))
+}
+
+/// Counts another notification for `session_id` in `state`, returning true
+/// while the session has had at most `max`.
+pub fn increment_and_check(state: &Path, session_id: &str, max: u32) -> Result<bool> {
+    let mut counts: Vec<(String, u32)> = read_session_map(state)?;
+
+    let count = match counts.iter_mut().find(|(session, _)| session == session_id) {
+        Some((_, count)) => {
+            *count = count.saturating_add(1);
+            *count
+        }
+        None => {
+            counts.push((session_id.to_string(), 1));
+            1
+        }
+    };
+    write_session_map(state, &counts)?;
+
+    Ok(count <= max)
+}
+
+/// Records `session_id` in the state file, returning true if it had not
+/// been seen before.
+pub fn mark_and_check_session(state: &Path, session_id: &str) -> Result<bool> {
+    let seen = match fs::read_to_string(state) {
+        Ok(content) => content.lines().any(|line| line == session_id),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => return Err(e.into()),
+    };
+    if seen {
+        return Ok(false);
+    }
+
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(state)?;
+    writeln!(file, "{}", session_id)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_notifications_enabled_by_default() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+
+        assert!(notifications_enabled(&temp_dir.path().join(DISABLED_FILE)));
+    }
+
+    #[test]
+    fn test_set_enabled_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join(DISABLED_FILE);
+
+        set_enabled(&state, false).unwrap();
+        assert!(!notifications_enabled(&state));
+        set_enabled(&state, false).unwrap();
+        assert!(!notifications_enabled(&state));
+
+        set_enabled(&state, true).unwrap();
+        assert!(notifications_enabled(&state));
+        set_enabled(&state, true).unwrap();
+        assert!(notifications_enabled(&state));
+    }
+
+    #[test]
+    fn test_bump_badge_increments() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join(BADGE_FILE);
+
+        assert_eq!(bump_badge(&state).unwrap(), 1);
+        assert_eq!(bump_badge(&state).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&state).unwrap(), "2\n");
+
+        fs::write(&state, "garbage").unwrap();
+        assert_eq!(bump_badge(&state).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_clear_badge_resets() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join(BADGE_FILE);
+
+        bump_badge(&state).unwrap();
+        bump_badge(&state).unwrap();
+        clear_badge(&state).unwrap();
+        clear_badge(&state).unwrap();
+        assert_eq!(bump_badge(&state).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_since_last_first_notification() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join(LAST_NOTIFIED_FILE);
+
+        assert_eq!(
+            since_last(&state, "session-1", SystemTime::now()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_since_last_subsequent_notification() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join(LAST_NOTIFIED_FILE);
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        since_last(&state, "session-1", start).unwrap();
+        assert_eq!(
+            since_last(&state, "session-1", start + Duration::from_secs(90)).unwrap(),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            since_last(&state, "session-2", start + Duration::from_secs(100)).unwrap(),
+            None
+        );
+        assert_eq!(
+            since_last(&state, "session-1", start + Duration::from_secs(120)).unwrap(),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn test_session_map_round_trip() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join("map");
+
+        assert!(read_session_map::<u32>(&state).unwrap().is_empty());
+
+        let entries = vec![("a\tb".to_string(), 1u32), ("c".to_string(), 2)];
+        write_session_map(&state, &entries).unwrap();
+        assert_eq!(read_session_map::<u32>(&state).unwrap(), entries);
+
+        fs::write(&state, "a\t1\nno value\nb\tx\n").unwrap();
+        assert_eq!(
+            read_session_map::<u32>(&state).unwrap(),
+            vec![("a".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_increment_and_check_cap() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join(SESSION_COUNTS_FILE);
+
+        // Under and at the cap
+        assert!(increment_and_check(&state, "session-1", 2).unwrap());
+        assert!(increment_and_check(&state, "session-1", 2).unwrap());
+        // Over the cap
+        assert!(!increment_and_check(&state, "session-1", 2).unwrap());
+        assert!(!increment_and_check(&state, "session-1", 2).unwrap());
+    }
+
+    #[test]
+    fn test_increment_and_check_counts_sessions_separately() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join(SESSION_COUNTS_FILE);
+
+        assert!(increment_and_check(&state, "session-1", 1).unwrap());
+        assert!(!increment_and_check(&state, "session-1", 1).unwrap());
+        assert!(increment_and_check(&state, "session-2", 1).unwrap());
+        assert_eq!(
+            fs::read_to_string(&state).unwrap(),
+            "session-1\t2\nsession-2\t1\n"
+        );
+    }
+
+    #[test]
+    fn test_mark_and_check_session_first_seen() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join("state").join(SEEN_SESSIONS_FILE);
+
+        assert!(mark_and_check_session(&state, "session-1").unwrap());
+    }
+
+    #[test]
+    fn test_mark_and_check_session_repeat_suppressed() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join(SEEN_SESSIONS_FILE);
+
+        assert!(mark_and_check_session(&state, "session-1").unwrap());
+        assert!(!mark_and_check_session(&state, "session-1").unwrap());
+        assert!(mark_and_check_session(&state, "session-2").unwrap());
+    }
+
+    #[test]
+    fn test_mark_and_check_session_persists() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let state = temp_dir.path().join(SEEN_SESSIONS_FILE);
+        fs::write(&state, "session-1\nsession-2\n").unwrap();
+
+        assert!(!mark_and_check_session(&state, "session-2").unwrap());
+        assert!(mark_and_check_session(&state, "session-3").unwrap());
+        assert_eq!(
+            fs::read_to_string(&state).unwrap(),
+            "session-1\nsession-2\nsession-3\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::ffi::OsString;
+use std::io::IsTerminal;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Color is only used when stdout is a terminal and `NO_COLOR` is unset
+/// (see https://no-color.org).
+fn color_enabled() -> bool {
+    use_color(
+        std::env::var_os("NO_COLOR"),
+        std::io::stdout().is_terminal(),
+    )
+}
+
+/// Decides whether to color output given the value of `NO_COLOR` and
+/// whether stdout is a terminal. An empty `NO_COLOR` counts as unset.
+fn use_color(no_color: Option<OsString>, is_terminal: bool) -> bool {
+    let no_color = no_color.is_some_and(|v| !v.is_empty());
+    !no_color && is_terminal
+}
+
+fn paint(s: &str, color: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", color, s, RESET)
+    } else {
+        s.to_string()
+    }
+}
+
+/// Formats a success message, in green when color is enabled.
+pub fn ok(s: &str) -> String {
+    paint(s, GREEN, color_enabled())
+}
+
+/// Formats a warning, in yellow when color is enabled.
+pub fn warn(s: &str) -> String {
+    paint(s, YELLOW, color_enabled())
+}
+
+/// Formats a failure message, in red when color is enabled.
+pub fn err(s: &str) -> String {
+    paint(s, RED, color_enabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint_enabled() {
+        assert_eq!(paint("done", GREEN, true), "\x1b[32mdone\x1b[0m");
+        assert_eq!(paint("failed", RED, true), "\x1b[31mfailed\x1b[0m");
+    }
+
+    #[test]
+    fn test_paint_disabled() {
+        assert_eq!(paint("done", GREEN, false), "done");
+    }
+
+    #[test]
+    fn test_no_color_disables_ansi() {
+        assert!(!use_color(Some(OsString::from("1")), true));
+        assert!(!use_color(Some(OsString::from("1")), false));
+    }
+
+    #[test]
+    fn test_color_needs_a_terminal() {
+        assert!(use_color(None, true));
+        assert!(use_color(Some(OsString::new()), true));
+        assert!(!use_color(None, false));
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use std::process::Command;
+
+/// Tag that identifies our entries in the system log.
+pub const SYSLOG_TAG: &str = "claude-code-notification";
+
+/// Builds the `logger` invocation that writes `message` under `tag`. On
+/// macOS `logger` forwards to the unified log, elsewhere to syslog.
+pub fn syslog_command(tag: &str, message: &str) -> Command {
+    let mut command = Command::new("logger");
+    // `--` keeps messages starting with a dash from being read as options
+    command.arg("-t").arg(tag).arg("--").arg(message);
+    command
+}
+
+/// Writes `message` to the system log, failing if `logger` can't be run or
+/// exits unsuccessfully.
+pub fn log_to_syslog(tag: &str, message: &str) -> Result<()> {
+    let output = syslog_command(tag, message)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to execute logger: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "logger failed with exit code {:?}: {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syslog_command() {
+        let command = syslog_command(SYSLOG_TAG, "-Build: done");
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(command.get_program(), "logger");
+        assert_eq!(
+            args,
+            vec!["-t", "claude-code-notification", "--", "-Build: done"]
+        );
+    }
+}
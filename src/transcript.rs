@@ -0,0 +1,239 @@
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader};
+
+/// Front matter longer than this is treated as malformed, so a transcript
+/// that merely starts with `---` isn't read to the end.
+const MAX_FRONT_MATTER_LINES: usize = 64;
+
+/// Activity counts read from a Claude Code transcript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranscriptSummary {
+    pub tool_calls: usize,
+    pub turns: usize,
+}
+
+impl TranscriptSummary {
+    /// Formats the counts for the notification body, e.g. "12 tool calls, 3 turns".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} tool call{}, {} turn{}",
+            self.tool_calls,
+            if self.tool_calls == 1 { "" } else { "s" },
+            self.turns,
+            if self.turns == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Notification preferences from a transcript's front matter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptMeta {
+    pub sound: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Reads `sound:` and `title:` from a `---`-delimited metadata block at the
+/// start of the transcript at `path`. A missing or unreadable transcript,
+/// absent front matter, or an unterminated block gives empty metadata.
+pub fn read_transcript_front_matter(path: &str) -> TranscriptMeta {
+    let Ok(file) = fs::File::open(path) else {
+        return TranscriptMeta::default();
+    };
+    let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+    if lines.next().as_deref().map(str::trim_end) != Some("---") {
+        return TranscriptMeta::default();
+    }
+
+    let mut meta = TranscriptMeta::default();
+    for line in lines.take(MAX_FRONT_MATTER_LINES) {
+        let line = line.trim();
+        if line == "---" {
+            return meta;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = unquote(value.trim());
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "sound" => meta.sound = Some(value.to_string()),
+            "title" => meta.title = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    // Never closed, so it wasn't front matter after all
+    TranscriptMeta::default()
+}
+
+/// Strips one pair of matching single or double quotes.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| {
+            value
+                .strip_prefix(quote)
+                .and_then(|rest| rest.strip_suffix(quote))
+        })
+        .unwrap_or(value)
+}
+
+/// Reads the transcript at `path` and counts its tool calls and assistant
+/// turns, or `None` if it can't be read.
+pub fn summarize_transcript(path: &str) -> Option<TranscriptSummary> {
+    let contents = fs::read_to_string(path).ok()?;
+    Some(summarize_lines(&contents))
+}
+
+/// Counts entries in transcript JSONL. Assistant replies are split across
+/// several entries sharing a message id, so turns count distinct ids.
+/// Malformed lines, such as a partially written last line, are skipped.
+fn summarize_lines(contents: &str) -> TranscriptSummary {
+    let mut summary = TranscriptSummary::default();
+    let mut message_ids = HashSet::new();
+
+    for entry in contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+    {
+        if entry["type"] != "assistant" {
+            continue;
+        }
+
+        let message = &entry["message"];
+        match message["id"].as_str() {
+            Some(id) => {
+                if message_ids.insert(id.to_string()) {
+                    summary.turns += 1;
+                }
+            }
+            None => summary.turns += 1,
+        }
+
+        if let Some(content) = message["content"].as_array() {
+            summary.tool_calls += content
+                .iter()
+                .filter(|block| block["type"] == "tool_use")
+                .count();
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const SAMPLE: &str = r#"{"type":"user","message":{"role":"user","content":"Fix the build"}}
+{"type":"assistant","message":{"id":"msg_1","content":[{"type":"text","text":"Looking"}]}}
+{"type":"assistant","message":{"id":"msg_1","content":[{"type":"tool_use","name":"Bash"}]}}
+{"type":"user","message":{"role":"user","content":[{"type":"tool_result"}]}}
+{"type":"assistant","message":{"id":"msg_2","content":[{"type":"tool_use","name":"Read"},{"type":"tool_use","name":"Edit"}]}}
+{"type":"summary","summary":"Fixed the build"}
+{"type":"assistant","message":{"id":"msg_3","content":[{"type":"text","text":"Done"}]}}
+"#;
+
+    #[test]
+    fn test_summarize_transcript_counts() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = temp_dir.path().join("transcript.jsonl");
+        fs::write(&path, SAMPLE).unwrap();
+
+        let summary = summarize_transcript(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            summary,
+            TranscriptSummary {
+                tool_calls: 3,
+                turns: 3
+            }
+        );
+        assert_eq!(summary.describe(), "3 tool calls, 3 turns");
+    }
+
+    #[test]
+    fn test_summarize_skips_malformed_lines() {
+        let contents = format!("not json\n{}{{\"type\":\"assistant\",\"mess", SAMPLE);
+
+        assert_eq!(summarize_lines(&contents).tool_calls, 3);
+    }
+
+    #[test]
+    fn test_summarize_missing_transcript() {
+        assert_eq!(summarize_transcript("/nonexistent/transcript.jsonl"), None);
+    }
+
+    fn write_transcript(dir: &TempDir, contents: &str) -> String {
+        let path = dir.path().join("transcript.md");
+        fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_front_matter_present() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_transcript(
+            &temp_dir,
+            "---\n# preferences\nsound: Ping\ntitle: \"Deploy: prod\"\nowner: me\n---\n{\"type\":\"user\"}\n",
+        );
+
+        assert_eq!(
+            read_transcript_front_matter(&path),
+            TranscriptMeta {
+                sound: Some("Ping".to_string()),
+                title: Some("Deploy: prod".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_front_matter_absent() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+        let path = write_transcript(&temp_dir, SAMPLE);
+
+        assert_eq!(
+            read_transcript_front_matter(&path),
+            TranscriptMeta::default()
+        );
+        assert_eq!(
+            read_transcript_front_matter("/nonexistent/transcript.jsonl"),
+            TranscriptMeta::default()
+        );
+    }
+
+    #[test]
+    fn test_front_matter_malformed() {
+        let temp_dir = TempDir::new().expect("Failed to create temporary directory");
+
+        // Never closed
+        let path = write_transcript(&temp_dir, "---\nsound: Ping\n{\"type\":\"user\"}\n");
+        assert_eq!(
+            read_transcript_front_matter(&path),
+            TranscriptMeta::default()
+        );
+
+        // Lines without a key and empty values are skipped
+        let path = write_transcript(&temp_dir, "---\nnot a pair\nsound:\ntitle: 'Hi'\n---\n");
+        assert_eq!(
+            read_transcript_front_matter(&path),
+            TranscriptMeta {
+                sound: None,
+                title: Some("Hi".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_describe_singular() {
+        let summary = TranscriptSummary {
+            tool_calls: 1,
+            turns: 1,
+        };
+        assert_eq!(summary.describe(), "1 tool call, 1 turn");
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// Prefix for warnings printed to stderr unless a sink overrides it.
+pub const DEFAULT_WARNING_PREFIX: &str = "Warning: ";
+
+/// Prefix for `--verbose` diagnostics printed to stderr.
+pub const DIAGNOSTIC_PREFIX: &str = "debug: ";
+
+/// Where non-fatal problems, such as a sound that failed to play, are
+/// reported. Defaults to stderr; library users can collect them instead.
+#[derive(Clone)]
+pub enum WarningSink {
+    /// Print each warning to stderr after the prefix.
+    Stderr(String),
+    /// Pass each warning, without a prefix, to a callback.
+    Callback(Arc<dyn Fn(&str) + Send + Sync>),
+}
+
+impl WarningSink {
+    pub fn callback<F: Fn(&str) + Send + Sync + 'static>(f: F) -> Self {
+        WarningSink::Callback(Arc::new(f))
+    }
+
+    pub fn warn(&self, message: &str) {
+        match self {
+            WarningSink::Stderr(prefix) => eprintln!("{}{}", prefix, message),
+            WarningSink::Callback(f) => f(message),
+        }
+    }
+}
+
+impl Default for WarningSink {
+    fn default() -> Self {
+        WarningSink::Stderr(DEFAULT_WARNING_PREFIX.to_string())
+    }
+}
+
+impl fmt::Debug for WarningSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningSink::Stderr(prefix) => f.debug_tuple("Stderr").field(prefix).finish(),
+            WarningSink::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+impl PartialEq for WarningSink {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (WarningSink::Stderr(a), WarningSink::Stderr(b)) => a == b,
+            (WarningSink::Callback(a), WarningSink::Callback(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Step-by-step diagnostics from `--verbose`. Messages are only passed to
+/// the sink when `level` is at least their own level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostics {
+    pub level: u8,
+    pub sink: WarningSink,
+}
+
+impl Diagnostics {
+    pub fn new(level: u8, sink: WarningSink) -> Self {
+        Diagnostics { level, sink }
+    }
+
+    pub fn enabled(&self, level: u8) -> bool {
+        level > 0 && self.level >= level
+    }
+
+    pub fn log(&self, level: u8, message: &str) {
+        if self.enabled(level) {
+            self.sink.warn(message);
+        }
+    }
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Diagnostics::new(0, WarningSink::Stderr(DIAGNOSTIC_PREFIX.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_callback_receives_warnings() {
+        let collected = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let collected = Arc::clone(&collected);
+            WarningSink::callback(move |msg| collected.lock().unwrap().push(msg.to_string()))
+        };
+
+        sink.warn("first");
+        sink.clone().warn("second");
+
+        assert_eq!(*collected.lock().unwrap(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_diagnostics_levels() {
+        let collected = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let collected = Arc::clone(&collected);
+            WarningSink::callback(move |msg| collected.lock().unwrap().push(msg.to_string()))
+        };
+
+        Diagnostics::new(0, sink.clone()).log(1, "hidden");
+        let diagnostics = Diagnostics::new(1, sink);
+        diagnostics.log(1, "shown");
+        diagnostics.log(2, "too detailed");
+
+        assert_eq!(*collected.lock().unwrap(), ["shown"]);
+    }
+
+    #[test]
+    fn test_sink_equality() {
+        let sink = WarningSink::callback(|_| {});
+
+        assert_eq!(WarningSink::default(), WarningSink::default());
+        assert_ne!(
+            WarningSink::default(),
+            WarningSink::Stderr("notify: ".into())
+        );
+        assert_eq!(sink, sink.clone());
+        assert_ne!(sink, WarningSink::callback(|_| {}));
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::push::{self, PushRequest};
+use crate::NotificationInput;
+
+/// Builds the JSON body posted to the webhook.
+pub fn webhook_payload(input: &NotificationInput, title: &str, id: &str) -> Value {
+    json!({
+        "id": id,
+        "title": title,
+        "message": input.message,
+        "session_id": input.session_id,
+    })
+}
+
+/// Builds the webhook POST as a push request to `url`.
+pub fn webhook_request(url: &str, input: &NotificationInput, title: &str, id: &str) -> PushRequest {
+    PushRequest {
+        url: url.to_string(),
+        headers: Vec::new(),
+        body: webhook_payload(input, title, id),
+    }
+}
+
+/// POSTs the notification as JSON to `url`, failing on non-2xx responses.
+pub fn post_webhook(url: &str, input: &NotificationInput, title: &str, id: &str) -> Result<()> {
+    post_webhook_with(url, input, title, id, push::send_request)
+}
+
+fn post_webhook_with<F>(
+    url: &str,
+    input: &NotificationInput,
+    title: &str,
+    id: &str,
+    send: F,
+) -> Result<()>
+where
+    F: FnOnce(&PushRequest) -> Result<()>,
+{
+    send(&webhook_request(url, input, title, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn input() -> NotificationInput {
+        NotificationInput {
+            session_id: "abc123".to_string(),
+            transcript_path: "/tmp/transcript.jsonl".to_string(),
+            message: "Build finished".to_string(),
+            title: None,
+            hook_event_name: None,
+            stop_hook_active: None,
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_post_webhook_payload() {
+        let sent = RefCell::new(None);
+
+        post_webhook_with(
+            "https://hooks.example.com/notify",
+            &input(),
+            "Claude Code",
+            "0123456789abcdef",
+            |request| {
+                *sent.borrow_mut() = Some(request.clone());
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        let request = sent.into_inner().unwrap();
+        assert_eq!(request.url, "https://hooks.example.com/notify");
+        assert!(request.headers.is_empty());
+        assert_eq!(
+            request.body,
+            json!({
+                "id": "0123456789abcdef",
+                "title": "Claude Code",
+                "message": "Build finished",
+                "session_id": "abc123",
+            })
+        );
+    }
+
+    #[test]
+    fn test_post_webhook_propagates_transport_error() {
+        let result = post_webhook_with(
+            "https://hooks.example.com",
+            &input(),
+            "Claude Code",
+            "0123456789abcdef",
+            |_| anyhow::bail!("connection refused"),
+        );
+
+        assert!(result.is_err());
+    }
+}
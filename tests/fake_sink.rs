@@ -0,0 +1,72 @@
+use claude_code_notification::{run, NotificationOptions, RecordingSink, Sound, SoundPolicy};
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// Options that record notifications in `sink` and "play" sounds with
+/// `true`, so nothing reaches the real system.
+fn fake_options(sink: &Arc<RecordingSink>) -> NotificationOptions {
+    NotificationOptions {
+        sink: Some(sink.clone()),
+        player: Some("true".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_json_input_is_shown_with_title_body_and_sound() {
+    let sink = Arc::new(RecordingSink::new());
+    let options = NotificationOptions {
+        sound: Sound::Hero,
+        ..fake_options(&sink)
+    };
+    let input = r#"{"session_id": "abc", "transcript_path": "/tmp/t.jsonl", "message": "Tests passed", "title": "CI"}"#;
+
+    run(Cursor::new(input), &options).unwrap();
+
+    let shown = sink.shown();
+    assert_eq!(shown.len(), 1);
+    assert_eq!(shown[0].title, "CI");
+    assert_eq!(shown[0].body, "Tests passed");
+    assert_eq!(shown[0].sound, Some(Sound::Hero));
+}
+
+#[test]
+fn test_defaults_fill_missing_title_and_message() {
+    let sink = Arc::new(RecordingSink::new());
+    let options = NotificationOptions {
+        hook_event: Some("Stop".to_string()),
+        locale: Some("en".to_string()),
+        ..fake_options(&sink)
+    };
+    let input = r#"{"session_id": "abc", "transcript_path": "/tmp/t.jsonl"}"#;
+
+    run(Cursor::new(input), &options).unwrap();
+
+    let shown = sink.shown();
+    assert_eq!(shown[0].title, "Claude Code");
+    assert_eq!(shown[0].body, "Claude finished");
+    assert_eq!(shown[0].sound, Some(Sound::Glass));
+}
+
+#[test]
+fn test_error_policy_drops_sound_for_success() {
+    let sink = Arc::new(RecordingSink::new());
+    let options = NotificationOptions {
+        sound_policy: SoundPolicy::Error,
+        ..fake_options(&sink)
+    };
+
+    run(
+        Cursor::new(r#"{"session_id": "a", "transcript_path": "", "message": "All done"}"#),
+        &options,
+    )
+    .unwrap();
+    run(
+        Cursor::new(r#"{"session_id": "a", "transcript_path": "", "message": "Build failed"}"#),
+        &options,
+    )
+    .unwrap();
+
+    let sounds: Vec<_> = sink.shown().into_iter().map(|n| n.sound).collect();
+    assert_eq!(sounds, [None, Some(Sound::Glass)]);
+}